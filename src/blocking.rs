@@ -0,0 +1,242 @@
+//! Blocking variant of the client API for synchronous callers. Calls are built by `calls` and checked, signed and
+//! recorded by the same code as in `client`, only sent by the blocking client of `http`.
+//!
+//! Like the blocking reqwest client, it must not be called inside async runtime.
+
+use std::time::Instant;
+
+use futures::executor::block_on;
+use indexmap::IndexMap;
+
+pub use crate::calls::PrivateCall;
+pub use crate::client::{
+    create_signature, private_curl, public_curl, public_path, sign_request, urlencode, ApiResponse,
+    ClientError, HttpRequest, NonceGen, SignatureError, SignedRequest, SquidCreds,
+};
+use crate::{
+    client::{self, Transport},
+    http,
+};
+
+// The blocking client shared by the process. Its futures are ready without waiting, so they are completed by
+// `block_on` of the calling thread.
+struct BlockingTransport;
+
+impl Transport for BlockingTransport {
+    async fn send(
+        &self,
+        request: HttpRequest,
+    ) -> reqwest::Result<(reqwest::StatusCode, ApiResponse, Option<bool>)> {
+        let request = request.build_blocking(http::blocking_client());
+        let started = Instant::now();
+        let response = request.send()?;
+        let new_connection = http::is_new_connection(response.extensions());
+        let status = response.status();
+        let text = response.text()?;
+        let latency = started.elapsed();
+        Ok((status, ApiResponse { text, latency }, new_connection))
+    }
+}
+
+/// Public request, e.g. built by `calls::ticker`.
+pub fn public_request(uri_path: &str) -> reqwest::Result<ApiResponse> {
+    block_on(client::public_request_over(&BlockingTransport, uri_path))
+}
+
+pub fn private_request(
+    uri_path: &str,
+    data: IndexMap<&str, String>,
+    creds: &SquidCreds,
+) -> Result<ApiResponse, ClientError> {
+    block_on(client::private_request_over(
+        &BlockingTransport,
+        uri_path,
+        data,
+        creds,
+    ))
+}
+
+/// Sends signed request as it is, without API-Key header when `api_key` is None.
+pub fn send_signed(signed: SignedRequest, api_key: Option<&str>) -> reqwest::Result<ApiResponse> {
+    block_on(client::send_signed_over(
+        &BlockingTransport,
+        signed,
+        api_key,
+    ))
+}
+
+/// Signed request to private endpoint, e.g. "Balance", with fresh nonce followed by `params`.
+/// State changing calls are checked by the safety guard first, nothing is signed when they are refused.
+pub fn private_call(
+    endpoint: &str,
+    params: &[(&str, &str)],
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+) -> Result<ApiResponse, ClientError> {
+    block_on(client::private_call_over(
+        &BlockingTransport,
+        endpoint,
+        params,
+        creds,
+        nonce_gen,
+    ))
+}
+
+/// Sends the call built by `calls`, e.g. `send_call(&calls::add_order(&order), &creds, &mut nonce_gen)`.
+pub fn send_call(
+    call: &PrivateCall,
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+) -> Result<ApiResponse, ClientError> {
+    private_call(&call.endpoint, &call.params(), creds, nonce_gen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calls, clock::FakeClock, http::HttpConfig};
+    use proptest::prelude::*;
+    use std::sync::{Arc, OnceLock};
+
+    const SECRET: &str = "c3F1aWQtdGVzdC1zZWNyZXQ=";
+
+    // Method, URL, headers and body of the request, as it is handed over to the connection.
+    type Sent = (
+        reqwest::Method,
+        String,
+        Vec<(String, Vec<u8>)>,
+        Option<Vec<u8>>,
+    );
+
+    fn headers(map: &reqwest::header::HeaderMap) -> Vec<(String, Vec<u8>)> {
+        map.iter()
+            .map(|(name, value)| (name.to_string(), value.as_bytes().to_vec()))
+            .collect()
+    }
+
+    // Clients are shared by all cases, the blocking one starts its own thread.
+    static ASYNC_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    static BLOCKING_CLIENT: OnceLock<reqwest::blocking::Client> = OnceLock::new();
+
+    fn sent_async(request: HttpRequest) -> Sent {
+        let client = ASYNC_CLIENT.get_or_init(|| {
+            HttpConfig::default()
+                .build()
+                .expect("Async client is built")
+        });
+        let request = request
+            .build(client)
+            .build()
+            .expect("Async request is built");
+        (
+            request.method().clone(),
+            request.url().to_string(),
+            headers(request.headers()),
+            request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(<[u8]>::to_vec),
+        )
+    }
+
+    fn sent_blocking(request: HttpRequest) -> Sent {
+        let client = BLOCKING_CLIENT.get_or_init(|| {
+            HttpConfig::default()
+                .build_blocking()
+                .expect("Blocking client is built")
+        });
+        let request = request
+            .build_blocking(client)
+            .build()
+            .expect("Blocking request is built");
+        (
+            request.method().clone(),
+            request.url().to_string(),
+            headers(request.headers()),
+            request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(<[u8]>::to_vec),
+        )
+    }
+
+    fn assert_identical(request: HttpRequest) {
+        assert_eq!(sent_async(request.clone()), sent_blocking(request));
+    }
+
+    fn signed(params: &[(&str, &str)]) -> SignedRequest {
+        let mut nonce_gen =
+            NonceGen::with_clock(Arc::new(FakeClock::stopped_at(1_616_492_376_594)));
+        let data = client::call_data(params, &mut nonce_gen);
+        sign_request("/0/private/TradeBalance", &data, SECRET).expect("Secret is valid")
+    }
+
+    #[test]
+    fn public_requests_are_identical() {
+        assert_identical(HttpRequest::public(&public_path(
+            "Ticker",
+            &[("pair", "XBTUSD,ETHXBT")],
+        )));
+    }
+
+    #[test]
+    fn signed_requests_are_identical() {
+        let signed = signed(&[("asset", "ZUSD")]);
+        assert_identical(HttpRequest::signed(signed.clone(), Some("squid-key")));
+        assert_identical(HttpRequest::signed(signed, None));
+    }
+
+    #[test]
+    fn signed_request_carries_key_signature_and_body() {
+        let signed = signed(&[("asset", "ZUSD")]);
+        let (method, url, headers, body) =
+            sent_blocking(HttpRequest::signed(signed.clone(), Some("squid-key")));
+        assert_eq!(method, reqwest::Method::POST);
+        assert_eq!(url, signed.url());
+        assert_eq!(
+            headers,
+            vec![
+                ("api-key".to_string(), b"squid-key".to_vec()),
+                ("api-sign".to_string(), signed.signature.into_bytes()),
+            ]
+        );
+        assert_eq!(body, Some(b"nonce=1616492376594&asset=ZUSD".to_vec()));
+    }
+
+    #[test]
+    fn safety_guard_runs_without_async_runtime() {
+        let mut nonce_gen =
+            NonceGen::with_clock(Arc::new(FakeClock::stopped_at(1_616_492_376_594)));
+        let refused = send_call(
+            &calls::cancel_all_orders(),
+            &SquidCreds::default(),
+            &mut nonce_gen,
+        );
+        assert!(
+            matches!(refused, Err(ClientError::Unsafe(ref e)) if e.rule() == "live_trading"),
+            "{refused:?}"
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn requests_of_any_params_are_identical(
+            query in proptest::collection::vec(("[a-z]{1,12}", "[A-Za-z0-9,.]{0,12}"), 0..8),
+            params in proptest::collection::vec(("[a-z\\[\\]0-9]{1,12}", any::<String>()), 0..8),
+            api_key in "[A-Za-z0-9+/=]{0,56}",
+        ) {
+            let query: Vec<(&str, &str)> = query
+                .iter()
+                .map(|(key, val)| (key.as_str(), val.as_str()))
+                .collect();
+            let params: Vec<(&str, &str)> = params
+                .iter()
+                .map(|(key, val)| (key.as_str(), val.as_str()))
+                .collect();
+            let public = HttpRequest::public(&public_path("Ticker", &query));
+            prop_assert_eq!(sent_async(public.clone()), sent_blocking(public));
+            let private = HttpRequest::signed(signed(&params), Some(&api_key));
+            prop_assert_eq!(sent_async(private.clone()), sent_blocking(private));
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! Endpoints and params of API calls, built once for `client` and `blocking`, so both send the same requests.
+//!
+//! Public calls are paths with query, e.g. "/0/public/Ticker?pair=XBTUSD". Private calls are `PrivateCall`s, to which
+//! nonce and validate are added when they are sent.
+
+use crate::{
+    client::{indexed_params, nested_params, public_path},
+    models::TradingPairAll,
+    order::{ConstraintError, OrderRequest},
+};
+
+/// Private endpoint, e.g. "Balance", with its params.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrivateCall {
+    pub endpoint: String,
+    pub params: Vec<(String, String)>,
+}
+
+impl PrivateCall {
+    pub fn new(endpoint: &str, params: &[(&str, &str)]) -> PrivateCall {
+        PrivateCall {
+            endpoint: endpoint.to_string(),
+            params: params
+                .iter()
+                .map(|(key, val)| (key.to_string(), val.to_string()))
+                .collect(),
+        }
+    }
+
+    pub fn params(&self) -> Vec<(&str, &str)> {
+        self.params
+            .iter()
+            .map(|(key, val)| (key.as_str(), val.as_str()))
+            .collect()
+    }
+}
+
+pub fn system_status() -> String {
+    public_path("SystemStatus", &[])
+}
+
+pub fn assets(params: &[(&str, &str)]) -> String {
+    public_path("Assets", params)
+}
+
+pub fn asset_pairs(params: &[(&str, &str)]) -> String {
+    public_path("AssetPairs", params)
+}
+
+pub fn ticker(params: &[(&str, &str)]) -> String {
+    public_path("Ticker", params)
+}
+
+pub fn ohlc(params: &[(&str, &str)]) -> String {
+    public_path("OHLC", params)
+}
+
+pub fn order_book(params: &[(&str, &str)]) -> String {
+    public_path("Depth", params)
+}
+
+pub fn recent_trades(params: &[(&str, &str)]) -> String {
+    public_path("Trades", params)
+}
+
+pub fn recent_spreads(params: &[(&str, &str)]) -> String {
+    public_path("Spread", params)
+}
+
+pub fn open_orders() -> PrivateCall {
+    PrivateCall::new("OpenOrders", &[("trades", "true")])
+}
+
+pub fn add_order(order: &OrderRequest) -> PrivateCall {
+    let params = order.params();
+    let params: Vec<(&str, &str)> = params
+        .iter()
+        .map(|(key, val)| (*key, val.as_str()))
+        .collect();
+    PrivateCall::new("AddOrder", &params)
+}
+
+/// AddOrder of the order, when it passes constraints of its pair from AssetPairs, see `OrderRequest::check`.
+pub fn add_order_checked(
+    order: &OrderRequest,
+    pair: &TradingPairAll,
+) -> Result<PrivateCall, ConstraintError> {
+    order.check(pair)?;
+    Ok(add_order(order))
+}
+
+/// Replaces open order with a new one, which gets new txid.
+pub fn edit_order(params: &[(&str, &str)]) -> PrivateCall {
+    PrivateCall::new("EditOrder", params)
+}
+
+/// Changes open order in place, it keeps its txid and queue priority where possible.
+pub fn amend_order(params: &[(&str, &str)]) -> PrivateCall {
+    PrivateCall::new("AmendOrder", params)
+}
+
+/// Cancels order by its txid, or all orders with given userref or cl_ord_id.
+pub fn cancel_order(txid: &str) -> PrivateCall {
+    PrivateCall::new("CancelOrder", &[("txid", txid)])
+}
+
+pub fn cancel_all_orders() -> PrivateCall {
+    PrivateCall::new("CancelAll", &[])
+}
+
+/// Places up to MAX_BATCH_ORDERS orders for the same pair in one call.
+/// Every order has its own params, e.g. [("type", "sell"), ("ordertype", "limit"), ...], and `params` apply to all of them.
+pub fn add_order_batch(params: &[(&str, &str)], orders: &[Vec<(&str, &str)>]) -> PrivateCall {
+    let nested = nested_params("orders", orders);
+    let mut params = params.to_vec();
+    params.extend(nested.iter().map(|(key, val)| (key.as_str(), val.as_str())));
+    PrivateCall::new("AddOrderBatch", &params)
+}
+
+/// Cancels up to MAX_BATCH_CANCELS orders given by txid or userref in one call.
+pub fn cancel_order_batch(txids: &[&str]) -> PrivateCall {
+    let indexed = indexed_params("orders", txids);
+    let params: Vec<(&str, &str)> = indexed
+        .iter()
+        .map(|(key, val)| (key.as_str(), val.as_str()))
+        .collect();
+    PrivateCall::new("CancelOrderBatch", &params)
+}
+
+/// Dead man's switch: all orders are cancelled, unless it is called again within `timeout` seconds.
+/// Timeout 0 disarms it.
+pub fn cancel_all_orders_after(timeout: u32) -> PrivateCall {
+    PrivateCall::new("CancelAllOrdersAfter", &[("timeout", &timeout.to_string())])
+}
+
+pub fn balance() -> PrivateCall {
+    PrivateCall::new("Balance", &[])
+}
+
+pub fn extended_balance() -> PrivateCall {
+    PrivateCall::new("BalanceEx", &[])
+}
+
+pub fn trade_balance(params: &[(&str, &str)]) -> PrivateCall {
+    PrivateCall::new("TradeBalance", params)
+}
+
+pub fn closed_orders(params: &[(&str, &str)]) -> PrivateCall {
+    PrivateCall::new("ClosedOrders", params)
+}
+
+pub fn query_orders(params: &[(&str, &str)]) -> PrivateCall {
+    PrivateCall::new("QueryOrders", params)
+}
+
+pub fn trades_history(params: &[(&str, &str)]) -> PrivateCall {
+    PrivateCall::new("TradesHistory", params)
+}
+
+pub fn query_trades(params: &[(&str, &str)]) -> PrivateCall {
+    PrivateCall::new("QueryTrades", params)
+}
+
+pub fn open_positions(params: &[(&str, &str)]) -> PrivateCall {
+    PrivateCall::new("OpenPositions", params)
+}
+
+pub fn ledgers(params: &[(&str, &str)]) -> PrivateCall {
+    PrivateCall::new("Ledgers", params)
+}
+
+pub fn query_ledgers(params: &[(&str, &str)]) -> PrivateCall {
+    PrivateCall::new("QueryLedgers", params)
+}
+
+pub fn trade_volume(params: &[(&str, &str)]) -> PrivateCall {
+    PrivateCall::new("TradeVolume", params)
+}
@@ -0,0 +1,983 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc, OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+use hmac::{Hmac, Mac};
+use indexmap::IndexMap;
+use sha2::{Digest, Sha256, Sha512};
+use tracing::{debug, instrument, trace};
+
+use crate::{
+    calls::{self, PrivateCall},
+    clock::{Clock, SynchronizedClock},
+    http, latency, metrics,
+    models::TradingPairAll,
+    order::{ConstraintError, OrderRequest},
+    safety::{self, SafetyError},
+};
+
+pub const API_URL: &str = "https://api.kraken.com";
+/// Base URL used instead of API_URL, e.g. to point requests to a local mock.
+pub const API_URL_ENV: &str = "SQUID_API_URL";
+pub const API_KEY_ENV: &str = "API_KEY";
+pub const API_SEC_ENV: &str = "API_SEC";
+/// When set, every request prints an equivalent curl command to stderr before it is sent.
+pub const PRINT_CURL_ENV: &str = "SQUID_PRINT_CURL";
+/// When set, printed curl commands contain the real API key instead of $API_KEY.
+pub const CURL_SHOW_KEY_ENV: &str = "SQUID_CURL_SHOW_KEY";
+/// When set, orders are only validated by the server and never placed, see `validate_only`.
+pub const VALIDATE_ONLY_ENV: &str = "SQUID_VALIDATE_ONLY";
+/// Limits of orders in one AddOrderBatch and CancelOrderBatch call.
+pub const MAX_BATCH_ORDERS: usize = 15;
+pub const MAX_BATCH_CANCELS: usize = 50;
+
+static API_URL_OVERRIDE: OnceLock<String> = OnceLock::new();
+
+/// Base URL of all requests: the one set by `set_api_url`, SQUID_API_URL env variable or production API_URL.
+pub fn api_url() -> String {
+    API_URL_OVERRIDE
+        .get()
+        .cloned()
+        .or_else(|| std::env::var(API_URL_ENV).ok())
+        .unwrap_or_else(|| API_URL.to_string())
+}
+
+/// Redirects all requests of the process to other base URL. Can be done only once.
+pub fn set_api_url(url: String) {
+    API_URL_OVERRIDE
+        .set(url)
+        .expect("API base URL can be set only once");
+}
+
+static VALIDATE_ONLY_OVERRIDE: OnceLock<bool> = OnceLock::new();
+
+// Endpoints placing orders, which accept validate param.
+pub(crate) const VALIDATED_ENDPOINTS: [&str; 3] = ["AddOrder", "EditOrder", "AddOrderBatch"];
+
+/// Dry run switch: the one set by `set_validate_only` or SQUID_VALIDATE_ONLY env variable.
+/// When it is on, every AddOrder, EditOrder and AddOrderBatch call is sent with validate=true.
+pub fn validate_only() -> bool {
+    VALIDATE_ONLY_OVERRIDE
+        .get()
+        .copied()
+        .unwrap_or_else(|| std::env::var_os(VALIDATE_ONLY_ENV).is_some())
+}
+
+/// Turns dry run of order placing calls on or off for the whole process. Can be done only once.
+pub fn set_validate_only(validate: bool) {
+    VALIDATE_ONLY_OVERRIDE
+        .set(validate)
+        .expect("Validate only switch can be set only once");
+}
+
+#[derive(Debug)]
+pub struct SquidCreds {
+    pub key: String,
+    pub secret: String,
+    /// Env variable of the key, which replaces it in printed curl commands.
+    pub key_env: String,
+}
+
+impl Default for SquidCreds {
+    fn default() -> Self {
+        SquidCreds {
+            key: String::new(),
+            secret: String::new(),
+            key_env: API_KEY_ENV.to_string(),
+        }
+    }
+}
+
+impl SquidCreds {
+    pub fn load_from_env() -> SquidCreds {
+        SquidCreds {
+            key: std::env::var(API_KEY_ENV).expect("API_KEY not found in environment"),
+            secret: std::env::var(API_SEC_ENV).expect("API_SEC not fount in environment"),
+            key_env: API_KEY_ENV.to_string(),
+        }
+    }
+
+    /// Same as `load_from_env`, but returns None instead of panicking when credentials are not set.
+    pub fn try_load_from_env() -> Option<SquidCreds> {
+        Some(SquidCreds {
+            key: std::env::var(API_KEY_ENV).ok()?,
+            secret: std::env::var(API_SEC_ENV).ok()?,
+            key_env: API_KEY_ENV.to_string(),
+        })
+    }
+}
+
+// Nonce has to grow with every request signed with the API key, also between concurrently run scenarios,
+// so the last one is shared by all default generators.
+static LAST_NONCE: OnceLock<Arc<AtomicI64>> = OnceLock::new();
+
+#[derive(Debug, Clone)]
+pub struct NonceGen {
+    clock: Arc<dyn Clock>,
+    last: Arc<AtomicI64>,
+}
+
+/// Generator with the synchronized clock and the last nonce shared by the process.
+impl Default for NonceGen {
+    fn default() -> Self {
+        NonceGen {
+            clock: Arc::new(SynchronizedClock),
+            last: LAST_NONCE.get_or_init(Arc::default).clone(),
+        }
+    }
+}
+
+impl NonceGen {
+    /// Generator with its own last nonce, reading time from the clock.
+    pub fn with_clock(clock: Arc<dyn Clock>) -> NonceGen {
+        NonceGen {
+            clock,
+            last: Arc::default(),
+        }
+    }
+
+    /// Clock of the nonces, e.g. for order times, which should agree with them.
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// Milliseconds of the clock, always higher than the last nonce.
+    #[instrument(skip_all)]
+    pub fn get_nonce(&mut self) -> String {
+        let now = self.clock.now_ms();
+        let last = self
+            .last
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+                Some(now.max(last + 1))
+            })
+            .expect("Nonce update never fails");
+        let nonce = now.max(last + 1);
+        debug!(nonce = "<redacted>", "nonce generated");
+        nonce.to_string()
+    }
+}
+
+#[derive(Debug)]
+pub struct ApiResponse {
+    pub text: String,
+    pub latency: Duration,
+}
+
+// Builder of the request for the async or the blocking client, which have the same methods.
+macro_rules! build_request {
+    ($request:expr, $client:expr) => {{
+        let request = $request;
+        let mut builder = $client.request(request.method, request.url);
+        for (name, value) in request.headers {
+            builder = builder.header(name, value);
+        }
+        match request.body {
+            Some(body) => builder.body(body),
+            None => builder,
+        }
+    }};
+}
+
+/// Method, URL, headers and body of a request. Both the async and the blocking client send it as it is, so they
+/// send the same requests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub method: reqwest::Method,
+    pub url: String,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: Option<String>,
+}
+
+impl HttpRequest {
+    pub fn public(uri_path: &str) -> HttpRequest {
+        HttpRequest {
+            method: reqwest::Method::GET,
+            url: format!("{}{uri_path}", api_url()),
+            headers: Vec::new(),
+            body: None,
+        }
+    }
+
+    /// Signed private request, without API-Key header when `api_key` is None.
+    pub fn signed(signed: SignedRequest, api_key: Option<&str>) -> HttpRequest {
+        let mut headers = Vec::new();
+        if let Some(api_key) = api_key {
+            headers.push(("API-Key", api_key.to_string()));
+        }
+        headers.push(("API-Sign", signed.signature.clone()));
+        HttpRequest {
+            method: reqwest::Method::POST,
+            url: signed.url(),
+            headers,
+            body: Some(signed.body),
+        }
+    }
+
+    pub fn build(self, client: &reqwest::Client) -> reqwest::RequestBuilder {
+        build_request!(self, client)
+    }
+
+    pub fn build_blocking(
+        self,
+        client: &reqwest::blocking::Client,
+    ) -> reqwest::blocking::RequestBuilder {
+        build_request!(self, client)
+    }
+}
+
+/// Client, which sends built requests: the async one of `http`, or the blocking one of `blocking`. Calls are
+/// checked, signed and recorded by the same code, whichever client sends them.
+pub(crate) trait Transport {
+    /// Sends the request, returns its status, response and whether the connection is new, when it is known.
+    async fn send(
+        &self,
+        request: HttpRequest,
+    ) -> reqwest::Result<(reqwest::StatusCode, ApiResponse, Option<bool>)>;
+}
+
+/// The async client shared by the process.
+pub(crate) struct AsyncTransport;
+
+impl Transport for AsyncTransport {
+    async fn send(
+        &self,
+        request: HttpRequest,
+    ) -> reqwest::Result<(reqwest::StatusCode, ApiResponse, Option<bool>)> {
+        let request = request.build(http::client());
+        let started = Instant::now();
+        let response = request.send().await?;
+        let new_connection = http::is_new_connection(response.extensions());
+        let status = response.status();
+        let text = response.text().await?;
+        let latency = started.elapsed();
+        Ok((status, ApiResponse { text, latency }, new_connection))
+    }
+}
+
+/// Records latency and errors of the response in the latency report and metrics.
+pub(crate) fn observe(endpoint: &str, latency: Duration, new_connection: Option<bool>, text: &str) {
+    latency::record(endpoint, latency, new_connection);
+    metrics::observe_latency(endpoint, latency);
+    metrics::count_errors(endpoint, text);
+}
+
+pub async fn public_request(uri_path: &str) -> reqwest::Result<ApiResponse> {
+    public_request_over(&AsyncTransport, uri_path).await
+}
+
+#[instrument(skip(transport), fields(endpoint = latency::endpoint_name(uri_path)))]
+pub(crate) async fn public_request_over(
+    transport: &impl Transport,
+    uri_path: &str,
+) -> reqwest::Result<ApiResponse> {
+    if std::env::var_os(PRINT_CURL_ENV).is_some() {
+        eprintln!("{}", public_curl(uri_path));
+    }
+    let (status, response, new_connection) = transport.send(HttpRequest::public(uri_path)).await?;
+    observe(
+        latency::endpoint_name(uri_path),
+        response.latency,
+        new_connection,
+        &response.text,
+    );
+    debug!(%status, latency_ms = response.latency.as_millis() as u64, new_connection, "public response received");
+    trace!(body = %response.text);
+    Ok(response)
+}
+
+/// Path with query of public endpoint, e.g. ("Ticker", [("pair", "XBTUSD")]) -> "/0/public/Ticker?pair=XBTUSD".
+pub fn public_path(endpoint: &str, params: &[(&str, &str)]) -> String {
+    let query: Vec<String> = params
+        .iter()
+        .map(|(key, val)| format!("{key}={val}"))
+        .collect();
+    if query.is_empty() {
+        format!("/0/public/{endpoint}")
+    } else {
+        format!("/0/public/{endpoint}?{}", query.join("&"))
+    }
+}
+
+pub async fn get_system_status() -> reqwest::Result<ApiResponse> {
+    public_request(&calls::system_status()).await
+}
+
+pub async fn get_assets(params: &[(&str, &str)]) -> reqwest::Result<ApiResponse> {
+    public_request(&calls::assets(params)).await
+}
+
+pub async fn get_asset_pairs(params: &[(&str, &str)]) -> reqwest::Result<ApiResponse> {
+    public_request(&calls::asset_pairs(params)).await
+}
+
+pub async fn get_ticker(params: &[(&str, &str)]) -> reqwest::Result<ApiResponse> {
+    public_request(&calls::ticker(params)).await
+}
+
+pub async fn get_ohlc(params: &[(&str, &str)]) -> reqwest::Result<ApiResponse> {
+    public_request(&calls::ohlc(params)).await
+}
+
+pub async fn get_order_book(params: &[(&str, &str)]) -> reqwest::Result<ApiResponse> {
+    public_request(&calls::order_book(params)).await
+}
+
+pub async fn get_recent_trades(params: &[(&str, &str)]) -> reqwest::Result<ApiResponse> {
+    public_request(&calls::recent_trades(params)).await
+}
+
+pub async fn get_recent_spreads(params: &[(&str, &str)]) -> reqwest::Result<ApiResponse> {
+    public_request(&calls::recent_spreads(params)).await
+}
+
+/// API secret, which cannot be used for signing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureError {
+    /// Secret is not valid base64.
+    InvalidSecret(base64::DecodeError),
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureError::InvalidSecret(e) => write!(f, "API secret is not valid base64: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+#[instrument(skip(secret, postdata, nonce))]
+pub fn create_signature(
+    secret: &str,
+    urlpath: &str,
+    postdata: &str,
+    nonce: &str,
+) -> Result<String, SignatureError> {
+    let msg_sha_input = nonce.to_string() + postdata;
+
+    let mut sha256 = Sha256::default();
+    sha256.update(msg_sha_input.as_bytes());
+
+    let data_hash = sha256.finalize();
+
+    let mut hmac_input = urlpath.as_bytes().to_vec();
+    for elem in data_hash {
+        hmac_input.push(elem);
+    }
+
+    let hmac_key = base64::decode(secret.as_bytes()).map_err(SignatureError::InvalidSecret)?;
+    let mut mac =
+        Hmac::<Sha512>::new_from_slice(&hmac_key[..]).expect("HMAC accepts key of any length");
+    mac.update(&hmac_input);
+    let signature = base64::encode(mac.finalize().into_bytes());
+    debug!(signature = "<redacted>", "request signed");
+    Ok(signature)
+}
+
+/// Percent-encoding of form key or value, e.g. "orders[0][price]" -> "orders%5B0%5D%5Bprice%5D".
+fn form_escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                escaped.push(byte as char)
+            }
+            _ => escaped.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    escaped
+}
+
+pub fn urlencode(data: &IndexMap<&str, String>) -> String {
+    let mut encoded = String::new();
+    for (key, val) in data {
+        encoded.push_str(&format!("{}={}&", form_escape(key), form_escape(val)));
+    }
+    encoded.pop();
+    encoded
+}
+
+/// Params of list entries with nested keys, e.g. ("orders", [[("price", "0.5")]]) -> [("orders[0][price]", "0.5")].
+pub fn nested_params(name: &str, entries: &[Vec<(&str, &str)>]) -> Vec<(String, String)> {
+    entries
+        .iter()
+        .enumerate()
+        .flat_map(|(index, entry)| {
+            entry
+                .iter()
+                .map(move |(key, val)| (format!("{name}[{index}][{key}]"), val.to_string()))
+        })
+        .collect()
+}
+
+/// Params of list values with indexed keys, e.g. ("orders", ["OABC"]) -> [("orders[0]", "OABC")].
+pub fn indexed_params(name: &str, values: &[&str]) -> Vec<(String, String)> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(index, val)| (format!("{name}[{index}]"), val.to_string()))
+        .collect()
+}
+
+/// Everything, what is sent in signed private request, except API key.
+#[derive(Debug, Clone)]
+pub struct SignedRequest {
+    pub uri_path: String,
+    pub signature: String,
+    pub body: String,
+}
+
+impl SignedRequest {
+    pub fn url(&self) -> String {
+        format!("{}{}", api_url(), self.uri_path)
+    }
+}
+
+/// Signs url encoded `data`, which starts with nonce.
+pub fn sign_request(
+    uri_path: &str,
+    data: &IndexMap<&str, String>,
+    api_secret: &str,
+) -> Result<SignedRequest, SignatureError> {
+    let nonce = data.get("nonce").expect("Signed data has nonce");
+    let body = urlencode(data);
+    let signature = create_signature(api_secret, uri_path, &body, nonce)?;
+    Ok(SignedRequest {
+        uri_path: uri_path.to_string(),
+        signature,
+        body,
+    })
+}
+
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+pub fn public_curl(uri_path: &str) -> String {
+    format!("curl {}", shell_quote(&format!("{}{uri_path}", api_url())))
+}
+
+/// Equivalent curl command of the private request. Nonce in `data` is signed as it is, so it should be a fresh one.
+/// API key is replaced with shell variable `key_env`, e.g. $API_KEY, unless `show_key` is set.
+pub fn private_curl(
+    uri_path: &str,
+    data: &IndexMap<&str, String>,
+    api_key: &str,
+    api_secret: &str,
+    key_env: &str,
+    show_key: bool,
+) -> Result<String, SignatureError> {
+    let signed = sign_request(uri_path, data, api_secret)?;
+    Ok(signed_curl(&signed, api_key, key_env, show_key))
+}
+
+fn signed_curl(signed: &SignedRequest, api_key: &str, key_env: &str, show_key: bool) -> String {
+    let key_header = if show_key {
+        shell_quote(&format!("API-Key: {api_key}"))
+    } else {
+        format!("\"API-Key: ${key_env}\"")
+    };
+    format!(
+        "curl -X POST {} -H {key_header} -H {} --data {}",
+        shell_quote(&signed.url()),
+        shell_quote(&format!("API-Sign: {}", signed.signature)),
+        shell_quote(&signed.body),
+    )
+}
+
+/// Signs the request and prints its curl command, when it is asked for with SQUID_PRINT_CURL.
+pub(crate) fn sign_for_sending(
+    uri_path: &str,
+    data: &IndexMap<&str, String>,
+    creds: &SquidCreds,
+) -> Result<SignedRequest, SignatureError> {
+    let signed = sign_request(uri_path, data, &creds.secret)?;
+    if std::env::var_os(PRINT_CURL_ENV).is_some() {
+        let show_key = std::env::var_os(CURL_SHOW_KEY_ENV).is_some();
+        eprintln!(
+            "{}",
+            signed_curl(&signed, &creds.key, &creds.key_env, show_key)
+        );
+    }
+    Ok(signed)
+}
+
+pub async fn private_request(
+    uri_path: &str,
+    data: IndexMap<&str, String>,
+    creds: &SquidCreds,
+) -> Result<ApiResponse, ClientError> {
+    private_request_over(&AsyncTransport, uri_path, data, creds).await
+}
+
+#[instrument(skip_all, fields(endpoint = latency::endpoint_name(uri_path)))]
+pub(crate) async fn private_request_over(
+    transport: &impl Transport,
+    uri_path: &str,
+    data: IndexMap<&str, String>,
+    creds: &SquidCreds,
+) -> Result<ApiResponse, ClientError> {
+    let signed = sign_for_sending(uri_path, &data, creds)?;
+    Ok(send_signed_over(transport, signed, Some(&creds.key)).await?)
+}
+
+/// Sends signed request as it is, without API-Key header when `api_key` is None.
+pub async fn send_signed(
+    signed: SignedRequest,
+    api_key: Option<&str>,
+) -> reqwest::Result<ApiResponse> {
+    send_signed_over(&AsyncTransport, signed, api_key).await
+}
+
+pub(crate) async fn send_signed_over(
+    transport: &impl Transport,
+    signed: SignedRequest,
+    api_key: Option<&str>,
+) -> reqwest::Result<ApiResponse> {
+    let endpoint = latency::endpoint_name(&signed.uri_path).to_string();
+    let (status, response, new_connection) =
+        transport.send(HttpRequest::signed(signed, api_key)).await?;
+    observe(&endpoint, response.latency, new_connection, &response.text);
+    debug!(%status, latency_ms = response.latency.as_millis() as u64, new_connection, "private response received");
+    trace!(body = %response.text);
+    Ok(response)
+}
+
+/// Error of private call, which is either refused by the safety guard or the constraints check, cannot be signed
+/// or failed on the way.
+#[derive(Debug)]
+pub enum ClientError {
+    Request(reqwest::Error),
+    Unsafe(SafetyError),
+    Signature(SignatureError),
+    /// Order breaks constraints of its pair, it is refused by `add_order_checked` before it is sent.
+    Constraint(ConstraintError),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Request(e) => write!(f, "{e}"),
+            ClientError::Unsafe(e) => write!(f, "refused by safety guard: {e}"),
+            ClientError::Signature(e) => write!(f, "cannot be signed: {e}"),
+            ClientError::Constraint(e) => write!(f, "breaks constraints of the pair: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(e: reqwest::Error) -> Self {
+        ClientError::Request(e)
+    }
+}
+
+impl From<SignatureError> for ClientError {
+    fn from(e: SignatureError) -> Self {
+        ClientError::Signature(e)
+    }
+}
+
+/// Signed request to private endpoint, e.g. "Balance", with fresh nonce followed by `params`.
+/// State changing calls are checked by the safety guard first, nothing is signed when they are refused.
+pub async fn private_call(
+    endpoint: &str,
+    params: &[(&str, &str)],
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+) -> Result<ApiResponse, ClientError> {
+    private_call_over(&AsyncTransport, endpoint, params, creds, nonce_gen).await
+}
+
+/// Sends the call built by `calls`, the same as `private_call`.
+pub async fn send_call(
+    call: &PrivateCall,
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+) -> Result<ApiResponse, ClientError> {
+    private_call(&call.endpoint, &call.params(), creds, nonce_gen).await
+}
+
+pub(crate) async fn private_call_over(
+    transport: &impl Transport,
+    endpoint: &str,
+    params: &[(&str, &str)],
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+) -> Result<ApiResponse, ClientError> {
+    let params = call_params(endpoint, params);
+    if let Err(e) = safety::check_over(transport, endpoint, &params, creds).await {
+        safety::log_refused(endpoint, &e);
+        return Err(ClientError::Unsafe(e));
+    }
+    let data = call_data(&params, nonce_gen);
+    let uri_path = format!("/0/private/{endpoint}");
+    private_request_over(transport, &uri_path, data, creds).await
+}
+
+/// Params of private call, with validate added to orders in dry run.
+pub fn call_params<'a>(endpoint: &str, params: &[(&'a str, &'a str)]) -> Vec<(&'a str, &'a str)> {
+    let mut params = params.to_vec();
+    if validate_only() && VALIDATED_ENDPOINTS.contains(&endpoint) {
+        params.push(("validate", "true"));
+    }
+    params
+}
+
+/// Signed data of private call: fresh nonce followed by the params.
+pub fn call_data<'a>(
+    params: &[(&'a str, &str)],
+    nonce_gen: &mut NonceGen,
+) -> IndexMap<&'a str, String> {
+    let mut data = IndexMap::from([("nonce", nonce_gen.get_nonce())]);
+    for (key, val) in params {
+        data.insert(*key, val.to_string());
+    }
+    data
+}
+
+pub async fn get_open_orders(
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+) -> Result<ApiResponse, ClientError> {
+    send_call(&calls::open_orders(), creds, nonce_gen).await
+}
+
+pub async fn add_order(
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+    order: &OrderRequest,
+) -> Result<ApiResponse, ClientError> {
+    send_call(&calls::add_order(order), creds, nonce_gen).await
+}
+
+/// Refused with `ClientError::Constraint` without sending, when the order breaks constraints of its pair.
+pub async fn add_order_checked(
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+    order: &OrderRequest,
+    pair: &TradingPairAll,
+) -> Result<ApiResponse, ClientError> {
+    let call = calls::add_order_checked(order, pair).map_err(ClientError::Constraint)?;
+    send_call(&call, creds, nonce_gen).await
+}
+
+pub async fn edit_order(
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+    params: &[(&str, &str)],
+) -> Result<ApiResponse, ClientError> {
+    send_call(&calls::edit_order(params), creds, nonce_gen).await
+}
+
+pub async fn amend_order(
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+    params: &[(&str, &str)],
+) -> Result<ApiResponse, ClientError> {
+    send_call(&calls::amend_order(params), creds, nonce_gen).await
+}
+
+pub async fn cancel_order(
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+    txid: &str,
+) -> Result<ApiResponse, ClientError> {
+    send_call(&calls::cancel_order(txid), creds, nonce_gen).await
+}
+
+pub async fn cancel_all_orders(
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+) -> Result<ApiResponse, ClientError> {
+    send_call(&calls::cancel_all_orders(), creds, nonce_gen).await
+}
+
+pub async fn add_order_batch(
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+    params: &[(&str, &str)],
+    orders: &[Vec<(&str, &str)>],
+) -> Result<ApiResponse, ClientError> {
+    send_call(&calls::add_order_batch(params, orders), creds, nonce_gen).await
+}
+
+pub async fn cancel_order_batch(
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+    txids: &[&str],
+) -> Result<ApiResponse, ClientError> {
+    send_call(&calls::cancel_order_batch(txids), creds, nonce_gen).await
+}
+
+pub async fn cancel_all_orders_after(
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+    timeout: u32,
+) -> Result<ApiResponse, ClientError> {
+    send_call(&calls::cancel_all_orders_after(timeout), creds, nonce_gen).await
+}
+
+pub async fn get_balance(
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+) -> Result<ApiResponse, ClientError> {
+    send_call(&calls::balance(), creds, nonce_gen).await
+}
+
+pub async fn get_extended_balance(
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+) -> Result<ApiResponse, ClientError> {
+    send_call(&calls::extended_balance(), creds, nonce_gen).await
+}
+
+pub async fn get_trade_balance(
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+    params: &[(&str, &str)],
+) -> Result<ApiResponse, ClientError> {
+    send_call(&calls::trade_balance(params), creds, nonce_gen).await
+}
+
+pub async fn get_closed_orders(
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+    params: &[(&str, &str)],
+) -> Result<ApiResponse, ClientError> {
+    send_call(&calls::closed_orders(params), creds, nonce_gen).await
+}
+
+pub async fn query_orders(
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+    params: &[(&str, &str)],
+) -> Result<ApiResponse, ClientError> {
+    send_call(&calls::query_orders(params), creds, nonce_gen).await
+}
+
+pub async fn get_trades_history(
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+    params: &[(&str, &str)],
+) -> Result<ApiResponse, ClientError> {
+    send_call(&calls::trades_history(params), creds, nonce_gen).await
+}
+
+pub async fn query_trades(
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+    params: &[(&str, &str)],
+) -> Result<ApiResponse, ClientError> {
+    send_call(&calls::query_trades(params), creds, nonce_gen).await
+}
+
+pub async fn get_open_positions(
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+    params: &[(&str, &str)],
+) -> Result<ApiResponse, ClientError> {
+    send_call(&calls::open_positions(params), creds, nonce_gen).await
+}
+
+pub async fn get_ledgers(
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+    params: &[(&str, &str)],
+) -> Result<ApiResponse, ClientError> {
+    send_call(&calls::ledgers(params), creds, nonce_gen).await
+}
+
+pub async fn query_ledgers(
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+    params: &[(&str, &str)],
+) -> Result<ApiResponse, ClientError> {
+    send_call(&calls::query_ledgers(params), creds, nonce_gen).await
+}
+
+pub async fn get_trade_volume(
+    creds: &SquidCreds,
+    nonce_gen: &mut NonceGen,
+    params: &[(&str, &str)],
+) -> Result<ApiResponse, ClientError> {
+    send_call(&calls::trade_volume(params), creds, nonce_gen).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FakeClock;
+    use proptest::prelude::*;
+
+    // Example of the signing docs of the API.
+    const EXAMPLE_SECRET: &str =
+        "kQH5HW/8p1uGOVjbgWA7FunAmGO8lsSUXNsu3eow76sz84Q18fWxnyRzBHCd3pd5nE9qa99HAZtuZuj6F1huXg==";
+    const EXAMPLE_NONCE: &str = "1616492376594";
+    const EXAMPLE_SIGNATURE: &str =
+        "4/dpxb3iT4tp/ZCVEwSnEsLxx0bqyhLpdfOpc6fn7OR8+UClSV5n9E6aSS8MPtnRfp32bAb0nmbRn6H8ndwLUQ==";
+
+    fn nonce(gen: &mut NonceGen) -> i64 {
+        gen.get_nonce().parse().expect("Nonce is a number")
+    }
+
+    #[test]
+    fn nonce_is_time_of_the_clock() {
+        let mut gen = NonceGen::with_clock(Arc::new(FakeClock::stopped_at(1_700_000_000_000)));
+        assert_eq!(nonce(&mut gen), 1_700_000_000_000);
+    }
+
+    #[test]
+    fn nonces_in_the_same_millisecond_grow() {
+        let mut gen = NonceGen::with_clock(Arc::new(FakeClock::stopped_at(1_700_000_000_000)));
+        let nonces: Vec<i64> = (0..3).map(|_| nonce(&mut gen)).collect();
+        assert_eq!(
+            nonces,
+            [1_700_000_000_000, 1_700_000_000_001, 1_700_000_000_002]
+        );
+    }
+
+    #[test]
+    fn nonces_grow_when_clock_goes_backwards() {
+        let clock = FakeClock::stopped_at(1_700_000_000_000);
+        let mut gen = NonceGen::with_clock(Arc::new(clock.clone()));
+        let first = nonce(&mut gen);
+        clock.advance(-5_000);
+        assert_eq!(nonce(&mut gen), first + 1);
+        clock.set(1_700_000_010_000);
+        assert_eq!(nonce(&mut gen), 1_700_000_010_000);
+    }
+
+    #[test]
+    fn generators_of_one_clock_share_nonces_only_when_cloned() {
+        let clock: Arc<dyn Clock> = Arc::new(FakeClock::stopped_at(1_700_000_000_000));
+        let mut gen = NonceGen::with_clock(clock.clone());
+        let mut clone = gen.clone();
+        let mut other = NonceGen::with_clock(clock);
+        assert_eq!(nonce(&mut gen), 1_700_000_000_000);
+        assert_eq!(nonce(&mut clone), 1_700_000_000_001);
+        assert_eq!(nonce(&mut other), 1_700_000_000_000);
+    }
+
+    fn example_data() -> IndexMap<&'static str, String> {
+        IndexMap::from([
+            ("nonce", EXAMPLE_NONCE.to_string()),
+            ("ordertype", "limit".to_string()),
+            ("pair", "XBTUSD".to_string()),
+            ("price", "37500".to_string()),
+            ("type", "buy".to_string()),
+            ("volume", "1.25".to_string()),
+        ])
+    }
+
+    /// Inverse of `urlencode`.
+    fn urldecode(encoded: &str) -> Vec<(String, String)> {
+        let unescape = |text: &str| {
+            let mut bytes = Vec::new();
+            let mut rest = text.as_bytes();
+            while let Some((&byte, tail)) = rest.split_first() {
+                if byte == b'%' {
+                    let hex = std::str::from_utf8(&tail[..2]).expect("Escape is ASCII");
+                    bytes.push(u8::from_str_radix(hex, 16).expect("Escape is hex"));
+                    rest = &tail[2..];
+                } else {
+                    bytes.push(byte);
+                    rest = tail;
+                }
+            }
+            String::from_utf8(bytes).expect("Decoded text is UTF-8")
+        };
+        encoded
+            .split('&')
+            .filter(|param| !param.is_empty())
+            .map(|param| {
+                let (key, val) = param.split_once('=').expect("Param has key=value format");
+                (unescape(key), unescape(val))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn example_is_encoded_as_in_docs() {
+        assert_eq!(
+            urlencode(&example_data()),
+            "nonce=1616492376594&ordertype=limit&pair=XBTUSD&price=37500&type=buy&volume=1.25"
+        );
+    }
+
+    #[test]
+    fn example_is_signed_as_in_docs() {
+        let signed = sign_request("/0/private/AddOrder", &example_data(), EXAMPLE_SECRET)
+            .expect("Example secret is valid");
+        assert_eq!(signed.signature, EXAMPLE_SIGNATURE);
+    }
+
+    #[test]
+    fn secret_which_is_not_base64_is_an_error() {
+        assert!(matches!(
+            create_signature("not base64!", "/0/private/Balance", "", EXAMPLE_NONCE),
+            Err(SignatureError::InvalidSecret(_))
+        ));
+    }
+
+    fn secret() -> impl Strategy<Value = String> {
+        proptest::collection::vec(any::<u8>(), 0..128).prop_map(base64::encode)
+    }
+
+    proptest! {
+        #[test]
+        fn encoding_round_trips(params in proptest::collection::vec((any::<String>(), any::<String>()), 0..8)) {
+            let data: IndexMap<&str, String> = params
+                .iter()
+                .map(|(key, val)| (key.as_str(), val.clone()))
+                .collect();
+            let expected: Vec<(String, String)> = data
+                .iter()
+                .map(|(key, val)| (key.to_string(), val.clone()))
+                .collect();
+            prop_assert_eq!(urldecode(&urlencode(&data)), expected);
+        }
+
+        #[test]
+        fn signing_is_deterministic(secret in secret(), path in ".*", nonce in "[0-9]{1,19}", body in ".*") {
+            prop_assert_eq!(
+                create_signature(&secret, &path, &body, &nonce),
+                create_signature(&secret, &path, &body, &nonce)
+            );
+        }
+
+        #[test]
+        fn signature_depends_on_path(secret in secret(), path in ".*", other in ".*", nonce in "[0-9]{1,19}", body in ".*") {
+            prop_assume!(path != other);
+            prop_assert_ne!(
+                create_signature(&secret, &path, &body, &nonce),
+                create_signature(&secret, &other, &body, &nonce)
+            );
+        }
+
+        #[test]
+        fn signature_depends_on_nonce(secret in secret(), path in ".*", nonce in "[0-9]{1,19}", other in "[0-9]{1,19}", body in ".*") {
+            prop_assume!(nonce != other);
+            prop_assert_ne!(
+                create_signature(&secret, &path, &body, &nonce),
+                create_signature(&secret, &path, &body, &other)
+            );
+        }
+
+        #[test]
+        fn signature_depends_on_body(secret in secret(), path in ".*", nonce in "[0-9]{1,19}", body in ".*", other in ".*") {
+            prop_assume!(body != other);
+            prop_assert_ne!(
+                create_signature(&secret, &path, &body, &nonce),
+                create_signature(&secret, &path, &other, &nonce)
+            );
+        }
+    }
+}
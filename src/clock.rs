@@ -0,0 +1,319 @@
+//! Offset of the server clock from the local one, estimated NTP-style from Time requests.
+//!
+//! Server time is read sometime between sending the request and receiving the response and it is truncated
+//! to whole seconds, so every sample limits the offset to an interval. Intersection of the intervals of all
+//! samples is the estimate, its half-width is the confidence bound.
+//!
+//! Current time is read from a `Clock`, so tests can replace the system clock with `FakeClock`.
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use tracing::{info, instrument};
+
+use crate::{
+    client::public_request,
+    models::{BaseResponse, ServerTimeType},
+};
+
+/// Number of Time requests of one estimate.
+pub const DEFAULT_SAMPLES: usize = 5;
+
+// Server time minus local time in ms, read by `SynchronizedClock`.
+static OFFSET_MS: AtomicI64 = AtomicI64::new(0);
+
+/// Source of current time.
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Milliseconds since the epoch.
+    fn now_ms(&self) -> i64;
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> i64 {
+        chrono::Utc::now().timestamp_millis()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum FakeTime {
+    Stopped(i64),
+    Skewed(i64),
+}
+
+/// Clock controlled by tests: stopped at given time, or running with the system clock shifted by a skew.
+/// Clones share the time.
+#[derive(Debug, Clone)]
+pub struct FakeClock {
+    time: Arc<Mutex<FakeTime>>,
+}
+
+impl FakeClock {
+    pub fn stopped_at(ms: i64) -> FakeClock {
+        FakeClock {
+            time: Arc::new(Mutex::new(FakeTime::Stopped(ms))),
+        }
+    }
+
+    pub fn skewed(skew_ms: i64) -> FakeClock {
+        FakeClock {
+            time: Arc::new(Mutex::new(FakeTime::Skewed(skew_ms))),
+        }
+    }
+
+    /// Stops the clock at given time.
+    pub fn set(&self, ms: i64) {
+        *self.time.lock().expect("Fake clock lock is poisoned") = FakeTime::Stopped(ms);
+    }
+
+    /// Moves the clock by given milliseconds, backwards when negative.
+    pub fn advance(&self, ms: i64) {
+        let mut time = self.time.lock().expect("Fake clock lock is poisoned");
+        *time = match *time {
+            FakeTime::Stopped(now) => FakeTime::Stopped(now + ms),
+            FakeTime::Skewed(skew) => FakeTime::Skewed(skew + ms),
+        };
+    }
+}
+
+impl Clock for FakeClock {
+    fn now_ms(&self) -> i64 {
+        match *self.time.lock().expect("Fake clock lock is poisoned") {
+            FakeTime::Stopped(now) => now,
+            FakeTime::Skewed(skew) => SystemClock.now_ms() + skew,
+        }
+    }
+}
+
+/// One Time request: local times of sending it and receiving the response, with server time in between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    pub sent_ms: i64,
+    pub server_secs: i64,
+    pub received_ms: i64,
+}
+
+impl Sample {
+    pub fn rtt_ms(&self) -> i64 {
+        self.received_ms - self.sent_ms
+    }
+
+    /// Lowest and highest offset, which agree with the sample.
+    fn bounds(&self) -> (i64, i64) {
+        let server_ms = self.server_secs * 1000;
+        (
+            server_ms - self.received_ms,
+            server_ms + 1000 - self.sent_ms,
+        )
+    }
+}
+
+/// Estimated server time minus local time, the real one is within `offset_ms` ± `error_ms`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Offset {
+    pub offset_ms: i64,
+    pub error_ms: i64,
+    pub samples: usize,
+    pub min_rtt_ms: i64,
+}
+
+impl Offset {
+    /// Whether the offset is certainly within `max_ms`, also at the far end of its confidence bound.
+    pub fn is_within(&self, max_ms: i64) -> bool {
+        self.offset_ms.abs() + self.error_ms < max_ms
+    }
+}
+
+impl fmt::Display for Offset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ms ± {} ms ({} samples, min RTT {} ms)",
+            self.offset_ms, self.error_ms, self.samples, self.min_rtt_ms
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum ClockError {
+    Request(reqwest::Error),
+    Response(String),
+}
+
+impl fmt::Display for ClockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClockError::Request(e) => write!(f, "Time request failed: {e}"),
+            ClockError::Response(e) => write!(f, "Time response is invalid: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ClockError {}
+
+impl From<reqwest::Error> for ClockError {
+    fn from(e: reqwest::Error) -> Self {
+        ClockError::Request(e)
+    }
+}
+
+/// Offset agreeing with all samples. None, when there are no samples.
+pub fn estimate(samples: &[Sample]) -> Option<Offset> {
+    let fastest = samples.iter().min_by_key(|sample| sample.rtt_ms())?;
+    let (low, high) = samples
+        .iter()
+        .map(Sample::bounds)
+        .fold((i64::MIN, i64::MAX), |(low, high), (l, h)| {
+            (low.max(l), high.min(h))
+        });
+    // Samples disagree when the server clock stepped in between, only the fastest one is trusted then.
+    let (low, high) = if low <= high {
+        (low, high)
+    } else {
+        fastest.bounds()
+    };
+    Some(Offset {
+        offset_ms: low + (high - low) / 2,
+        error_ms: (high - low + 1) / 2,
+        samples: samples.len(),
+        min_rtt_ms: fastest.rtt_ms(),
+    })
+}
+
+/// Sends one Time request, local times are read from the clock.
+pub async fn sample(clock: &dyn Clock) -> Result<Sample, ClockError> {
+    let sent_ms = clock.now_ms();
+    let response = public_request("/0/public/Time").await?;
+    let received_ms = clock.now_ms();
+    let body = serde_json::from_str::<BaseResponse<ServerTimeType>>(&response.text)
+        .map_err(|e| ClockError::Response(e.to_string()))?;
+    if !body.error.is_empty() {
+        return Err(ClockError::Response(body.error.join(", ")));
+    }
+    Ok(Sample {
+        sent_ms,
+        server_secs: body.result.unixtime,
+        received_ms,
+    })
+}
+
+/// Estimates the offset from given number of Time requests, at least one is sent.
+pub async fn measure(clock: &dyn Clock, samples: usize) -> Result<Offset, ClockError> {
+    let mut taken = Vec::with_capacity(samples);
+    for _ in 0..samples.max(1) {
+        taken.push(sample(clock).await?);
+    }
+    Ok(estimate(&taken).expect("At least one sample is taken"))
+}
+
+/// Measures the offset of the server from the clock and applies it to `SynchronizedClock`.
+#[instrument]
+pub async fn synchronize(clock: &dyn Clock) -> Result<Offset, ClockError> {
+    let offset = measure(clock, DEFAULT_SAMPLES).await?;
+    OFFSET_MS.store(offset.offset_ms, Ordering::SeqCst);
+    info!(%offset, "clock offset to the server applied");
+    Ok(offset)
+}
+
+/// Offset applied by the last `synchronize`, 0 before it.
+pub fn offset_ms() -> i64 {
+    OFFSET_MS.load(Ordering::SeqCst)
+}
+
+/// System clock corrected by the offset applied by `synchronize`, it reads server time.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SynchronizedClock;
+
+impl Clock for SynchronizedClock {
+    fn now_ms(&self) -> i64 {
+        SystemClock.now_ms() + offset_ms()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(sent_ms: i64, server_secs: i64, received_ms: i64) -> Sample {
+        Sample {
+            sent_ms,
+            server_secs,
+            received_ms,
+        }
+    }
+
+    #[test]
+    fn no_samples_no_estimate() {
+        assert_eq!(estimate(&[]), None);
+    }
+
+    #[test]
+    fn one_sample_is_bounded_by_rtt_and_server_resolution() {
+        // Server read 100.xxx s sometime between local 99.900 s and 100.100 s.
+        let offset = estimate(&[sample(99_900, 100, 100_100)]).expect("One sample");
+        assert_eq!(offset.offset_ms, 500);
+        assert_eq!(offset.error_ms, 600);
+        assert_eq!(offset.min_rtt_ms, 200);
+    }
+
+    #[test]
+    fn samples_narrow_the_estimate() {
+        let offset = estimate(&[
+            sample(10_000, 12, 10_100),
+            sample(10_800, 12, 10_900),
+            sample(11_850, 14, 11_900),
+        ])
+        .expect("Three samples");
+        assert_eq!((offset.offset_ms, offset.error_ms), (2_150, 50));
+        assert_eq!(offset.samples, 3);
+        assert_eq!(offset.min_rtt_ms, 50);
+    }
+
+    #[test]
+    fn disagreeing_samples_fall_back_to_the_fastest() {
+        let offset = estimate(&[sample(10_000, 10, 10_020), sample(20_000, 30, 20_100)])
+            .expect("Two samples");
+        assert_eq!((offset.offset_ms, offset.error_ms), (490, 510));
+        assert_eq!(offset.min_rtt_ms, 20);
+    }
+
+    #[test]
+    fn skew_just_over_the_threshold_is_not_within() {
+        let offset = |offset_ms, error_ms| Offset {
+            offset_ms,
+            error_ms,
+            samples: 1,
+            min_rtt_ms: 0,
+        };
+        assert!(offset(4_899, 100).is_within(5_000));
+        assert!(!offset(4_900, 100).is_within(5_000));
+        assert!(!offset(-4_900, 100).is_within(5_000));
+        assert!(!offset(4_000, 1_001).is_within(5_000));
+    }
+
+    #[test]
+    fn fake_clock_is_controlled() {
+        let clock = FakeClock::stopped_at(1_000);
+        let shared = clock.clone();
+        clock.advance(500);
+        assert_eq!(shared.now_ms(), 1_500);
+        clock.advance(-2_000);
+        assert_eq!(shared.now_ms(), -500);
+        clock.set(42);
+        assert_eq!(shared.now_ms(), 42);
+    }
+
+    #[test]
+    fn skewed_fake_clock_runs_with_the_system_clock() {
+        let clock = FakeClock::skewed(3_000);
+        let skew = clock.now_ms() - SystemClock.now_ms();
+        assert!((2_900..=3_000).contains(&skew), "skew {skew}");
+    }
+}
@@ -0,0 +1,278 @@
+//! HTTP client shared by all requests of the process, so connections are kept alive and reused across steps.
+//!
+//! Timeouts, user agent, proxy and CA bundle are configured with env variables.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+/// Timeout of whole request, including reading the response, in seconds.
+pub const TIMEOUT_ENV: &str = "SQUID_HTTP_TIMEOUT_SECS";
+/// Timeout of connecting to the server, in seconds.
+pub const CONNECT_TIMEOUT_ENV: &str = "SQUID_HTTP_CONNECT_TIMEOUT_SECS";
+/// How long an idle connection is kept open for reuse, in seconds.
+pub const POOL_IDLE_ENV: &str = "SQUID_HTTP_POOL_IDLE_SECS";
+pub const USER_AGENT_ENV: &str = "SQUID_USER_AGENT";
+/// Proxy of all requests, e.g. http://127.0.0.1:3128 or socks5h://127.0.0.1:1080.
+pub const PROXY_ENV: &str = "SQUID_PROXY";
+/// PEM file with certificates trusted in addition to the system ones, e.g. of an intercepting proxy.
+pub const CA_BUNDLE_ENV: &str = "SQUID_CA_BUNDLE";
+
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_POOL_IDLE_SECS: u64 = 90;
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+const PEM_BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+const PEM_END: &str = "-----END CERTIFICATE-----";
+
+static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+static BLOCKING_CLIENT: OnceLock<reqwest::blocking::Client> = OnceLock::new();
+
+// Local and remote address of connections, which already sent a request, with the time of the last one. The pool
+// does not reuse connections idle for longer than its idle timeout, so older ones are forgotten.
+static CONNECTIONS: Mutex<Option<HashMap<(SocketAddr, SocketAddr), Instant>>> = Mutex::new(None);
+// Idle timeout of the built clients, after which a connection is not reused.
+static POOL_IDLE_TIMEOUT: OnceLock<Duration> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpConfig {
+    pub timeout: Duration,
+    pub connect_timeout: Duration,
+    pub pool_idle_timeout: Duration,
+    pub user_agent: String,
+    pub proxy: Option<String>,
+    pub ca_bundle: Option<PathBuf>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        HttpConfig {
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+            connect_timeout: Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS),
+            pool_idle_timeout: Duration::from_secs(DEFAULT_POOL_IDLE_SECS),
+            user_agent: format!("growing_squid/{}", env!("CARGO_PKG_VERSION")),
+            proxy: None,
+            ca_bundle: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum HttpError {
+    InvalidEnv {
+        name: &'static str,
+        value: String,
+    },
+    CaBundle {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Client(reqwest::Error),
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpError::InvalidEnv { name, value } => {
+                write!(f, "{name} should be number of seconds, got {value}")
+            }
+            HttpError::CaBundle { path, source } => {
+                write!(f, "cannot read CA bundle {}: {source}", path.display())
+            }
+            HttpError::Client(e) => write!(f, "HTTP client cannot be built: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+impl From<reqwest::Error> for HttpError {
+    fn from(e: reqwest::Error) -> Self {
+        HttpError::Client(e)
+    }
+}
+
+fn env_secs(name: &'static str, default: Duration) -> Result<Duration, HttpError> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map(Duration::from_secs)
+            .map_err(|_| HttpError::InvalidEnv { name, value }),
+        Err(_) => Ok(default),
+    }
+}
+
+// Applies the configuration to the builder of the async or the blocking client, which have the same methods.
+macro_rules! configure {
+    ($config:expr, $builder:expr) => {{
+        let config: &HttpConfig = $config;
+        let _ = POOL_IDLE_TIMEOUT.set(config.pool_idle_timeout);
+        let mut builder = $builder
+            .timeout(config.timeout)
+            .connect_timeout(config.connect_timeout)
+            .pool_idle_timeout(config.pool_idle_timeout)
+            .tcp_keepalive(TCP_KEEPALIVE)
+            .user_agent(&config.user_agent);
+        if let Some(proxy) = &config.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+        for certificate in config.certificates()? {
+            builder = builder.add_root_certificate(certificate);
+        }
+        builder
+    }};
+}
+
+impl HttpConfig {
+    /// Defaults overridden by the env variables, which are set.
+    pub fn from_env() -> Result<HttpConfig, HttpError> {
+        let default = HttpConfig::default();
+        Ok(HttpConfig {
+            timeout: env_secs(TIMEOUT_ENV, default.timeout)?,
+            connect_timeout: env_secs(CONNECT_TIMEOUT_ENV, default.connect_timeout)?,
+            pool_idle_timeout: env_secs(POOL_IDLE_ENV, default.pool_idle_timeout)?,
+            user_agent: std::env::var(USER_AGENT_ENV).unwrap_or(default.user_agent),
+            proxy: std::env::var(PROXY_ENV).ok(),
+            ca_bundle: std::env::var_os(CA_BUNDLE_ENV).map(PathBuf::from),
+        })
+    }
+
+    fn certificates(&self) -> Result<Vec<reqwest::Certificate>, HttpError> {
+        let Some(path) = &self.ca_bundle else {
+            return Ok(Vec::new());
+        };
+        let pem = std::fs::read_to_string(path).map_err(|source| HttpError::CaBundle {
+            path: path.clone(),
+            source,
+        })?;
+        // Certificate::from_pem reads only the first certificate, so every one of the bundle is read separately.
+        Ok(pem
+            .split_inclusive(PEM_END)
+            .filter(|block| block.contains(PEM_BEGIN))
+            .map(|block| reqwest::Certificate::from_pem(block.as_bytes()))
+            .collect::<Result<_, _>>()?)
+    }
+
+    pub fn build(&self) -> Result<reqwest::Client, HttpError> {
+        Ok(configure!(self, reqwest::Client::builder()).build()?)
+    }
+
+    /// Client of the blocking API with the same configuration.
+    pub fn build_blocking(&self) -> Result<reqwest::blocking::Client, HttpError> {
+        Ok(configure!(self, reqwest::blocking::Client::builder()).build()?)
+    }
+}
+
+/// Builds the shared client from env variables, unless it is already built. Called at start, so wrong
+/// configuration is reported before any request.
+pub fn init() -> Result<(), HttpError> {
+    if CLIENT.get().is_none() {
+        let client = HttpConfig::from_env()?.build()?;
+        let _ = CLIENT.set(client);
+    }
+    Ok(())
+}
+
+/// The shared client, built from env variables on first use.
+pub fn client() -> &'static reqwest::Client {
+    CLIENT.get_or_init(|| {
+        HttpConfig::from_env()
+            .and_then(|config| config.build())
+            .unwrap_or_else(|e| panic!("{e}"))
+    })
+}
+
+/// The shared blocking client, built from env variables on first use. It must not be used inside async runtime.
+pub fn blocking_client() -> &'static reqwest::blocking::Client {
+    BLOCKING_CLIENT.get_or_init(|| {
+        HttpConfig::from_env()
+            .and_then(|config| config.build_blocking())
+            .unwrap_or_else(|e| panic!("{e}"))
+    })
+}
+
+/// Whether the response with these extensions came over a connection, which was not used before. None, when it is
+/// unknown, because the connection has no addresses, e.g. through some proxies.
+pub fn is_new_connection(extensions: &hyper::http::Extensions) -> Option<bool> {
+    let info = extensions.get::<hyper::client::connect::HttpInfo>()?;
+    let idle_timeout = POOL_IDLE_TIMEOUT
+        .get()
+        .copied()
+        .unwrap_or(Duration::from_secs(DEFAULT_POOL_IDLE_SECS));
+    let mut connections = CONNECTIONS.lock().expect("Connections lock is poisoned");
+    Some(note_connection(
+        connections.get_or_insert_with(HashMap::new),
+        (info.local_addr(), info.remote_addr()),
+        Instant::now(),
+        idle_timeout,
+    ))
+}
+
+// Remembers use of the connection, forgets connections, which are idle for too long to be reused.
+fn note_connection(
+    connections: &mut HashMap<(SocketAddr, SocketAddr), Instant>,
+    addrs: (SocketAddr, SocketAddr),
+    now: Instant,
+    idle_timeout: Duration,
+) -> bool {
+    connections.retain(|_, last_used| now.duration_since(*last_used) <= idle_timeout);
+    connections.insert(addrs, now).is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_certificate_of_ca_bundle_is_read() {
+        let config = HttpConfig {
+            ca_bundle: Some(
+                [env!("CARGO_MANIFEST_DIR"), "tests/fixtures/ca_bundle.pem"]
+                    .iter()
+                    .collect(),
+            ),
+            ..HttpConfig::default()
+        };
+        let certificates = config.certificates().expect("CA bundle is valid");
+        assert_eq!(certificates.len(), 2);
+        config.build().expect("Client trusts the bundle");
+    }
+
+    #[test]
+    fn connection_idle_for_longer_than_pool_timeout_is_new() {
+        let mut connections = HashMap::new();
+        let addrs = (
+            "127.0.0.1:50000".parse().unwrap(),
+            "127.0.0.1:443".parse().unwrap(),
+        );
+        let idle_timeout = Duration::from_secs(90);
+        let start = Instant::now();
+        assert!(note_connection(
+            &mut connections,
+            addrs,
+            start,
+            idle_timeout
+        ));
+        let reused = start + Duration::from_secs(60);
+        assert!(!note_connection(
+            &mut connections,
+            addrs,
+            reused,
+            idle_timeout
+        ));
+        // The pool closed it, so the same ports belong to another connection.
+        let later = reused + Duration::from_secs(91);
+        assert!(note_connection(
+            &mut connections,
+            addrs,
+            later,
+            idle_timeout
+        ));
+        assert_eq!(connections.len(), 1);
+    }
+}
@@ -0,0 +1,218 @@
+use serde::Serialize;
+use std::{collections::BTreeMap, fs::OpenOptions, io::Write, sync::Mutex, time::Duration};
+
+const LATENCY_HISTORY_ENV: &str = "LATENCY_HISTORY_FILE";
+const DEFAULT_LATENCY_HISTORY_FILE: &str = "latency_history.jsonl";
+
+// Scenarios run concurrently and every one of them gets its own World, so samples are gathered globally.
+static SAMPLES: Mutex<BTreeMap<String, Vec<Sample>>> = Mutex::new(BTreeMap::new());
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    latency: Duration,
+    // Request opened a connection instead of reusing a kept-alive one, so latency includes TCP and TLS handshakes.
+    // None when it is unknown.
+    new_connection: Option<bool>,
+}
+
+pub fn record(endpoint: &str, latency: Duration, new_connection: Option<bool>) {
+    SAMPLES
+        .lock()
+        .expect("Latency samples lock is poisoned")
+        .entry(endpoint.to_string())
+        .or_default()
+        .push(Sample {
+            latency,
+            new_connection,
+        });
+}
+
+/// Name of the endpoint used as a key in latency reports, e.g. "/0/public/AssetPairs?pair=XXBTZUSD" -> "AssetPairs".
+pub fn endpoint_name(uri_path: &str) -> &str {
+    let path = uri_path.split('?').next().unwrap_or_default();
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+#[derive(Debug, Serialize)]
+pub struct EndpointSummary {
+    pub endpoint: String,
+    pub count: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    /// Requests, which opened a new connection.
+    pub new_connections: usize,
+    /// Requests, of which it is unknown whether they opened a new connection.
+    pub unknown_connections: usize,
+    /// Median of requests over new and over reused connections, None when there are none.
+    pub p50_new_ms: Option<f64>,
+    pub p50_reused_ms: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunSummary {
+    pub timestamp: String,
+    pub endpoints: Vec<EndpointSummary>,
+}
+
+// Nearest-rank percentile, samples have to be sorted.
+fn percentile(sorted: &[Duration], pct: f64) -> Duration {
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn as_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn median_ms(samples: &[Sample], new_connection: bool) -> Option<f64> {
+    let mut sorted: Vec<Duration> = samples
+        .iter()
+        .filter(|sample| sample.new_connection == Some(new_connection))
+        .map(|sample| sample.latency)
+        .collect();
+    sorted.sort();
+    (!sorted.is_empty()).then(|| as_ms(percentile(&sorted, 50.0)))
+}
+
+/// Summary of samples recorded since the last `take`, which are removed, so the next run starts empty.
+pub fn take() -> RunSummary {
+    let samples = std::mem::take(&mut *SAMPLES.lock().expect("Latency samples lock is poisoned"));
+    let endpoints = samples
+        .iter()
+        .filter(|(_, samples)| !samples.is_empty())
+        .map(|(endpoint, samples)| {
+            let mut sorted: Vec<Duration> = samples.iter().map(|sample| sample.latency).collect();
+            sorted.sort();
+            EndpointSummary {
+                endpoint: endpoint.clone(),
+                count: sorted.len(),
+                p50_ms: as_ms(percentile(&sorted, 50.0)),
+                p95_ms: as_ms(percentile(&sorted, 95.0)),
+                max_ms: as_ms(*sorted.last().unwrap()),
+                new_connections: samples
+                    .iter()
+                    .filter(|sample| sample.new_connection == Some(true))
+                    .count(),
+                unknown_connections: samples
+                    .iter()
+                    .filter(|sample| sample.new_connection.is_none())
+                    .count(),
+                p50_new_ms: median_ms(samples, true),
+                p50_reused_ms: median_ms(samples, false),
+            }
+        })
+        .collect();
+    RunSummary {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        endpoints,
+    }
+}
+
+// Table of the summary, the endpoint column is as wide as the longest name.
+fn table(summary: &RunSummary) -> String {
+    let width = summary
+        .endpoints
+        .iter()
+        .map(|e| e.endpoint.len())
+        .chain(["endpoint".len()])
+        .max()
+        .unwrap_or_default();
+    let mut table = format!(
+        "{:<width$} {:>6} {:>10} {:>10} {:>10} {:>9} {:>12} {:>14} {:>15}\n",
+        "endpoint",
+        "count",
+        "p50 [ms]",
+        "p95 [ms]",
+        "max [ms]",
+        "new conn",
+        "unknown conn",
+        "p50 new [ms]",
+        "p50 reused [ms]"
+    );
+    let optional = |ms: Option<f64>| ms.map_or("-".to_string(), |ms| format!("{ms:.1}"));
+    for e in &summary.endpoints {
+        table.push_str(&format!(
+            "{:<width$} {:>6} {:>10.1} {:>10.1} {:>10.1} {:>9} {:>12} {:>14} {:>15}\n",
+            e.endpoint,
+            e.count,
+            e.p50_ms,
+            e.p95_ms,
+            e.max_ms,
+            e.new_connections,
+            e.unknown_connections,
+            optional(e.p50_new_ms),
+            optional(e.p50_reused_ms)
+        ));
+    }
+    table
+}
+
+/// Prints latency summary of the run and appends it as one line to the JSONL history file.
+/// Samples of the run are taken, so in monitor mode every report covers only its own run.
+pub fn report() {
+    let summary = take();
+    if summary.endpoints.is_empty() {
+        return;
+    }
+    println!("\nLatency per endpoint:");
+    print!("{}", table(&summary));
+
+    let history_path = std::env::var(LATENCY_HISTORY_ENV)
+        .unwrap_or_else(|_| DEFAULT_LATENCY_HISTORY_FILE.to_string());
+    let line = serde_json::to_string(&summary).expect("Cannot serialize latency summary");
+    let appended = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&history_path)
+        .and_then(|mut file| writeln!(file, "{line}"));
+    if let Err(e) = appended {
+        eprintln!("Cannot append latency summary to {history_path}: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary_of<'a>(summary: &'a RunSummary, endpoint: &str) -> Option<&'a EndpointSummary> {
+        summary.endpoints.iter().find(|e| e.endpoint == endpoint)
+    }
+
+    #[test]
+    fn every_run_summarizes_only_its_own_samples() {
+        record("TakeFirstRun", Duration::from_millis(10), Some(true));
+        record("TakeFirstRun", Duration::from_millis(20), None);
+        let first = take();
+        assert_eq!(summary_of(&first, "TakeFirstRun").map(|e| e.count), Some(2));
+
+        record("TakeSecondRun", Duration::from_millis(30), Some(false));
+        let second = take();
+        assert!(summary_of(&second, "TakeFirstRun").is_none());
+        let run = summary_of(&second, "TakeSecondRun").expect("Second run is summarized");
+        assert_eq!((run.count, run.max_ms), (1, 30.0));
+    }
+
+    #[test]
+    fn columns_are_aligned_with_long_endpoint_names() {
+        let endpoint = |endpoint: &str| EndpointSummary {
+            endpoint: endpoint.to_string(),
+            count: 1,
+            p50_ms: 1.0,
+            p95_ms: 1.0,
+            max_ms: 1.0,
+            new_connections: 1,
+            unknown_connections: 0,
+            p50_new_ms: Some(1.0),
+            p50_reused_ms: None,
+        };
+        let summary = RunSummary {
+            timestamp: String::new(),
+            endpoints: vec![endpoint("Time"), endpoint("CancelAllOrdersAfter")],
+        };
+        let table = table(&summary);
+        let widths: Vec<usize> = table.lines().map(str::len).collect();
+        assert_eq!(widths.len(), 3);
+        assert!(widths.iter().all(|width| *width == widths[0]), "{table}");
+    }
+}
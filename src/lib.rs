@@ -0,0 +1,15 @@
+//! Client for the Kraken REST API shared by the cucumber test suite and the `growing_squid` command line tool.
+
+pub mod blocking;
+pub mod calls;
+pub mod client;
+pub mod clock;
+pub mod http;
+pub mod latency;
+pub mod metrics;
+pub mod models;
+pub mod order;
+pub mod permissions;
+pub mod profile;
+pub mod safety;
+pub mod telemetry;
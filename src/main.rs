@@ -1,3 +1,269 @@
-fn main() {
-    println!("'cargo test' run tests");
+use clap::{Parser, Subcommand, ValueEnum};
+use growing_squid::{
+    client::{
+        call_data, call_params, private_call, private_curl, public_curl, public_path,
+        public_request, set_api_url, ApiResponse, ClientError, NonceGen, SquidCreds, API_URL_ENV,
+    },
+    http, permissions,
+    profile::{self, CredentialProfile},
+    safety, telemetry,
+};
+
+mod output;
+
+/// Ad-hoc calls to the Kraken REST API, signed the same way as in the test suite.
+/// Private calls take credentials from API_KEY and API_SEC env variables,
+/// or from variables of the environment selected with --env or SQUID_ENV.
+#[derive(Debug, Parser)]
+#[command(name = "growing_squid", version)]
+struct Cli {
+    /// Format of printed response result
+    #[arg(long, short, value_enum, default_value_t = OutputFormat::Json, global = true)]
+    output: OutputFormat,
+
+    /// Print equivalent curl command (with a fresh nonce and signature) instead of sending the request
+    #[arg(long, global = true)]
+    curl: bool,
+
+    /// Put the real API key into printed curl command instead of the variable holding it, e.g. $API_KEY
+    #[arg(long, global = true, requires = "curl")]
+    show_key: bool,
+
+    /// Environment from environments.toml (or SQUID_ENV_FILE), e.g. production
+    #[arg(long, global = true)]
+    env: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Json,
+    Table,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Server time
+    Time,
+    /// Permissions of the API key, probed by calls which change nothing on the account
+    Whoami,
+    /// Tradable asset pairs
+    Pairs {
+        /// Asset pair, e.g. XBTUSD. All pairs are returned when not given
+        #[arg(long)]
+        pair: Option<String>,
+        /// Limit returned info: info, leverage, fees or margin
+        #[arg(long)]
+        info: Option<String>,
+    },
+    /// User orders
+    Orders {
+        #[command(subcommand)]
+        command: OrdersCommand,
+    },
+    /// Any private endpoint, e.g. `private Balance` or `private ClosedOrders trades=true`
+    Private {
+        /// Name of the endpoint, the part after /0/private/
+        method: String,
+        /// Request parameters as key=value, nonce is added automatically
+        #[arg(value_parser = parse_param)]
+        params: Vec<(String, String)>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum OrdersCommand {
+    /// List open orders
+    Open,
+    /// Cancel all open orders
+    CancelAll,
+}
+
+fn parse_param(param: &str) -> Result<(String, String), String> {
+    param
+        .split_once('=')
+        .map(|(key, val)| (key.to_string(), val.to_string()))
+        .ok_or_else(|| format!("parameter '{param}' should have key=value format"))
+}
+
+fn pairs_path(pair: Option<&str>, info: Option<&str>) -> String {
+    let params: Vec<(&str, &str)> = [("pair", pair), ("info", info)]
+        .into_iter()
+        .filter_map(|(key, val)| val.map(|val| (key, val)))
+        .collect();
+    public_path("AssetPairs", &params)
+}
+
+fn creds_from_env(credentials: &CredentialProfile) -> SquidCreds {
+    credentials.load().unwrap_or_else(|| {
+        eprintln!(
+            "{} and {} have to be set in environment for private requests",
+            credentials.key_env, credentials.secret_env
+        );
+        std::process::exit(2);
+    })
+}
+
+/// Points requests to the selected environment. Returns its credential profile.
+fn select_environment(flag: Option<&str>) -> CredentialProfile {
+    let Some(name) = profile::selected(flag) else {
+        return CredentialProfile::default();
+    };
+    let profile = profile::load(&name).unwrap_or_else(|e| {
+        eprintln!("Cannot select environment: {e}");
+        std::process::exit(2);
+    });
+    if profile.fake_exchange {
+        eprintln!("Environment {name} uses the fake exchange, which runs only in the test suite");
+        std::process::exit(2);
+    }
+    safety::set_live(profile.is_live());
+    // SQUID_API_URL still overrides URL of the environment.
+    if let (Some(url), None) = (profile.rest_url, std::env::var_os(API_URL_ENV)) {
+        set_api_url(url);
+    }
+    profile.credentials
+}
+
+/// Endpoint and parameters of the request described by command line.
+enum ApiCall {
+    Public(String),
+    Private {
+        method: String,
+        params: Vec<(String, String)>,
+    },
+}
+
+/// Command, which sends more than one request, e.g. whoami.
+struct NotSingleCall;
+
+impl TryFrom<Command> for ApiCall {
+    type Error = NotSingleCall;
+
+    fn try_from(command: Command) -> Result<Self, Self::Error> {
+        let private = |method: &str, params: Vec<(String, String)>| ApiCall::Private {
+            method: method.to_string(),
+            params,
+        };
+        Ok(match command {
+            Command::Time => ApiCall::Public(public_path("Time", &[])),
+            Command::Whoami => return Err(NotSingleCall),
+            Command::Pairs { pair, info } => {
+                ApiCall::Public(pairs_path(pair.as_deref(), info.as_deref()))
+            }
+            Command::Orders {
+                command: OrdersCommand::Open,
+            } => private(
+                "OpenOrders",
+                vec![("trades".to_string(), "true".to_string())],
+            ),
+            Command::Orders {
+                command: OrdersCommand::CancelAll,
+            } => private("CancelAll", vec![]),
+            Command::Private { method, params } => private(&method, params),
+        })
+    }
+}
+
+fn param_refs(params: &[(String, String)]) -> Vec<(&str, &str)> {
+    params
+        .iter()
+        .map(|(key, val)| (key.as_str(), val.as_str()))
+        .collect()
+}
+
+/// State changing calls go through the safety guard, the same as in the test suite.
+async fn send(call: &ApiCall, credentials: &CredentialProfile) -> Result<ApiResponse, ClientError> {
+    match call {
+        ApiCall::Public(uri_path) => Ok(public_request(uri_path).await?),
+        ApiCall::Private { method, params } => {
+            let creds = creds_from_env(credentials);
+            private_call(
+                method,
+                &param_refs(params),
+                &creds,
+                &mut NonceGen::default(),
+            )
+            .await
+        }
+    }
+}
+
+/// Curl command of the call. State changing calls are checked by the safety guard before they are signed, the same
+/// as when they are sent.
+async fn curl(call: &ApiCall, credentials: &CredentialProfile, show_key: bool) -> String {
+    match call {
+        ApiCall::Public(uri_path) => public_curl(uri_path),
+        ApiCall::Private { method, params } => {
+            let creds = creds_from_env(credentials);
+            // Params as `send` would sign them, e.g. with validate in dry run.
+            let params = call_params(method, &param_refs(params));
+            if let Err(e) = safety::check(method, &params, &creds).await {
+                eprintln!("Request failed: {}", ClientError::Unsafe(e));
+                std::process::exit(2);
+            }
+            let data = call_data(&params, &mut NonceGen::default());
+            let uri_path = format!("/0/private/{method}");
+            private_curl(
+                &uri_path,
+                &data,
+                &creds.key,
+                &creds.secret,
+                &creds.key_env,
+                show_key,
+            )
+            .unwrap_or_else(|e| {
+                eprintln!("{e}");
+                std::process::exit(2);
+            })
+        }
+    }
+}
+
+/// Prints access of the key to every permission, as result of the output format.
+async fn whoami(credentials: &CredentialProfile, format: OutputFormat) {
+    let creds = creds_from_env(credentials);
+    let result: serde_json::Map<String, serde_json::Value> =
+        permissions::probe(&creds, &mut NonceGen::default())
+            .await
+            .into_iter()
+            .map(|(permission, access)| (permission.to_string(), access.to_string().into()))
+            .collect();
+    let response = serde_json::json!({ "error": [], "result": result });
+    output::print_response(&response.to_string(), format);
+}
+
+#[tokio::main]
+async fn main() {
+    telemetry::init();
+    let cli = Cli::parse();
+    if let Err(e) = http::init() {
+        eprintln!("Cannot configure HTTP client: {e}");
+        std::process::exit(2);
+    }
+    let credentials = select_environment(cli.env.as_deref());
+    let Ok(call) = ApiCall::try_from(cli.command) else {
+        if cli.curl {
+            eprintln!("whoami sends a request per permission, it cannot be printed with --curl");
+            std::process::exit(2);
+        }
+        whoami(&credentials, cli.output).await;
+        return;
+    };
+    if cli.curl {
+        println!("{}", curl(&call, &credentials, cli.show_key).await);
+        return;
+    }
+    let response = match send(&call, &credentials).await {
+        Ok(response) => response,
+        Err(e) => {
+            eprintln!("Request failed: {e}");
+            std::process::exit(2);
+        }
+    };
+    if !output::print_response(&response.text, cli.output) {
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,229 @@
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use std::{collections::BTreeMap, convert::Infallible, fmt::Write, net::SocketAddr, sync::Mutex};
+
+use crate::models::CriticalErrorResponse;
+
+// Upper bounds of latency histogram buckets in seconds, "+Inf" bucket is added on render.
+const LATENCY_BUCKETS: [f64; 9] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+#[derive(Debug, Default, Clone)]
+struct Histogram {
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if value <= bound {
+                *bucket += 1;
+            }
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+#[derive(Debug)]
+struct Metrics {
+    runs: u64,
+    scenarios: BTreeMap<&'static str, u64>,
+    latency: BTreeMap<String, Histogram>,
+    errors: BTreeMap<(String, String), u64>,
+}
+
+static METRICS: Mutex<Metrics> = Mutex::new(Metrics::new());
+
+fn metrics() -> std::sync::MutexGuard<'static, Metrics> {
+    METRICS.lock().expect("Metrics lock is poisoned")
+}
+
+impl Metrics {
+    const fn new() -> Metrics {
+        Metrics {
+            runs: 0,
+            scenarios: BTreeMap::new(),
+            latency: BTreeMap::new(),
+            errors: BTreeMap::new(),
+        }
+    }
+
+    fn observe_latency(&mut self, endpoint: &str, latency: std::time::Duration) {
+        self.latency
+            .entry(endpoint.to_string())
+            .or_default()
+            .observe(latency.as_secs_f64());
+    }
+
+    fn count_errors(&mut self, endpoint: &str, response_text: &str) {
+        let Ok(body) = serde_json::from_str::<CriticalErrorResponse>(response_text) else {
+            return;
+        };
+        for error in body.error {
+            let category = error.split(':').next().unwrap_or_default().to_string();
+            *self
+                .errors
+                .entry((endpoint.to_string(), category))
+                .or_default() += 1;
+        }
+    }
+
+    fn count_run(&mut self, passed: usize, failed: usize, skipped: usize) {
+        self.runs += 1;
+        for (result, count) in [("passed", passed), ("failed", failed), ("skipped", skipped)] {
+            *self.scenarios.entry(result).or_default() += count as u64;
+        }
+    }
+}
+
+pub fn observe_latency(endpoint: &str, latency: std::time::Duration) {
+    metrics().observe_latency(endpoint, latency);
+}
+
+/// Counts Kraken errors from response body by their category, e.g. "EGeneral:Invalid arguments" -> "EGeneral".
+pub fn count_errors(endpoint: &str, response_text: &str) {
+    metrics().count_errors(endpoint, response_text);
+}
+
+pub fn count_run(passed: usize, failed: usize, skipped: usize) {
+    metrics().count_run(passed, failed, skipped);
+}
+
+/// Renders all metrics in Prometheus text exposition format.
+pub fn render() -> String {
+    render_metrics(&metrics())
+}
+
+fn render_metrics(metrics: &Metrics) -> String {
+    let mut out = String::new();
+
+    out.push_str("# HELP squid_runs_total Number of finished runs of the feature suite.\n");
+    out.push_str("# TYPE squid_runs_total counter\n");
+    writeln!(out, "squid_runs_total {}", metrics.runs).unwrap();
+
+    out.push_str("# HELP squid_scenarios_total Number of finished scenarios by result.\n");
+    out.push_str("# TYPE squid_scenarios_total counter\n");
+    for (result, count) in &metrics.scenarios {
+        writeln!(out, "squid_scenarios_total{{result=\"{result}\"}} {count}").unwrap();
+    }
+
+    out.push_str("# HELP squid_request_duration_seconds Round trip time of API requests.\n");
+    out.push_str("# TYPE squid_request_duration_seconds histogram\n");
+    for (endpoint, histogram) in &metrics.latency {
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+            writeln!(
+                out,
+                "squid_request_duration_seconds_bucket{{endpoint=\"{endpoint}\",le=\"{bound}\"}} {count}"
+            )
+            .unwrap();
+        }
+        writeln!(
+            out,
+            "squid_request_duration_seconds_bucket{{endpoint=\"{endpoint}\",le=\"+Inf\"}} {}",
+            histogram.count
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "squid_request_duration_seconds_sum{{endpoint=\"{endpoint}\"}} {}",
+            histogram.sum
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "squid_request_duration_seconds_count{{endpoint=\"{endpoint}\"}} {}",
+            histogram.count
+        )
+        .unwrap();
+    }
+
+    out.push_str("# HELP squid_api_errors_total Errors returned by the API by category.\n");
+    out.push_str("# TYPE squid_api_errors_total counter\n");
+    for ((endpoint, category), count) in &metrics.errors {
+        writeln!(
+            out,
+            "squid_api_errors_total{{endpoint=\"{endpoint}\",category=\"{category}\"}} {count}"
+        )
+        .unwrap();
+    }
+    out
+}
+
+async fn handle(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let response = match (req.method(), req.uri().path()) {
+        (&Method::GET, "/metrics") => Response::builder()
+            .header("Content-Type", "text/plain; version=0.0.4")
+            .body(Body::from(render())),
+        _ => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty()),
+    };
+    Ok(response.expect("Cannot build metrics response"))
+}
+
+/// Serves "/metrics" endpoint in the background.
+pub fn serve(addr: SocketAddr) {
+    let make_service = make_service_fn(|_conn| async { Ok::<_, Infallible>(service_fn(handle)) });
+    let server = Server::bind(&addr).serve(make_service);
+    tracing::info!(%addr, "metrics endpoint is listening");
+    tokio::spawn(async move {
+        if let Err(e) = server.await {
+            tracing::error!("metrics server failed: {e}");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn metrics_are_rendered_in_prometheus_text_format() {
+        let mut metrics = Metrics::new();
+        metrics.count_run(3, 1, 2);
+        metrics.observe_latency("Time", Duration::from_millis(80));
+        metrics.observe_latency("Time", Duration::from_millis(3000));
+        metrics.count_errors(
+            "AddOrder",
+            r#"{"error":["EOrder:Insufficient funds","EGeneral:Invalid arguments:volume"]}"#,
+        );
+        metrics.count_errors("AddOrder", r#"{"error":["EOrder:Unknown order"]}"#);
+        metrics.count_errors("Time", r#"{"error":[],"result":{"unixtime":1}}"#);
+        metrics.count_errors("Time", "<html>Bad gateway</html>");
+        assert_eq!(
+            render_metrics(&metrics),
+            r#"# HELP squid_runs_total Number of finished runs of the feature suite.
+# TYPE squid_runs_total counter
+squid_runs_total 1
+# HELP squid_scenarios_total Number of finished scenarios by result.
+# TYPE squid_scenarios_total counter
+squid_scenarios_total{result="failed"} 1
+squid_scenarios_total{result="passed"} 3
+squid_scenarios_total{result="skipped"} 2
+# HELP squid_request_duration_seconds Round trip time of API requests.
+# TYPE squid_request_duration_seconds histogram
+squid_request_duration_seconds_bucket{endpoint="Time",le="0.05"} 0
+squid_request_duration_seconds_bucket{endpoint="Time",le="0.1"} 1
+squid_request_duration_seconds_bucket{endpoint="Time",le="0.25"} 1
+squid_request_duration_seconds_bucket{endpoint="Time",le="0.5"} 1
+squid_request_duration_seconds_bucket{endpoint="Time",le="1"} 1
+squid_request_duration_seconds_bucket{endpoint="Time",le="2.5"} 1
+squid_request_duration_seconds_bucket{endpoint="Time",le="5"} 2
+squid_request_duration_seconds_bucket{endpoint="Time",le="10"} 2
+squid_request_duration_seconds_bucket{endpoint="Time",le="30"} 2
+squid_request_duration_seconds_bucket{endpoint="Time",le="+Inf"} 2
+squid_request_duration_seconds_sum{endpoint="Time"} 3.08
+squid_request_duration_seconds_count{endpoint="Time"} 2
+# HELP squid_api_errors_total Errors returned by the API by category.
+# TYPE squid_api_errors_total counter
+squid_api_errors_total{endpoint="AddOrder",category="EGeneral"} 1
+squid_api_errors_total{endpoint="AddOrder",category="EOrder"} 2
+"#
+        );
+    }
+}
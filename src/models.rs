@@ -0,0 +1,442 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::{collections::HashMap, vec::Vec};
+
+#[derive(Deserialize, Debug)]
+pub struct BaseResponse<DetailType> {
+    pub error: Vec<String>,
+    pub result: DetailType,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CriticalErrorResponse {
+    pub error: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ServerTimeType {
+    pub unixtime: i64,
+    pub rfc1123: String,
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+pub struct XBTUSDRequest<TradingPair> {
+    pub XXBTZUSD: TradingPair,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Fee(pub u32, pub Decimal);
+
+#[derive(Debug, Deserialize)]
+pub struct TradingPairAll {
+    pub aclass_base: String,
+    pub aclass_quote: String,
+    pub altname: String,
+    pub base: String,
+    pub cost_decimals: u32,
+    /// Minimum cost of order in quote currency, not sent for every pair.
+    pub costmin: Option<Decimal>,
+    pub fee_volume_currency: String,
+    pub fees: Vec<Fee>,
+    pub fees_maker: Vec<Fee>,
+    pub leverage_buy: Vec<u8>,
+    pub leverage_sell: Vec<u8>,
+    pub lot: String,
+    pub lot_decimals: u8,
+    pub lot_multiplier: u8,
+    pub margin_call: u8,
+    pub margin_stop: u8,
+    pub ordermin: Decimal,
+    pub pair_decimals: u8,
+    pub quote: String,
+    pub tick_size: Option<Decimal>,
+    pub wsname: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TradingPairFees {
+    pub fee_volume_currency: String,
+    pub fees: Vec<Fee>,
+    pub fees_maker: Vec<Fee>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TradingPairLeverage {
+    pub leverage_buy: Vec<u8>,
+    pub leverage_sell: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TradingPairMargin {
+    pub margin_call: u8,
+    pub margin_level: u8, // BUG?: Shouldn't this be named margin_stop?
+                          // pub margin_stop: u8,
+}
+
+// NOTE: Example of trading pair response
+// {'error': [],
+//  'result': {'XXBTZUSD': {'aclass_base': 'currency',
+//                          'aclass_quote': 'currency',
+//                          'altname': 'XBTUSD',
+//                          'base': 'XXBT',
+//                          'cost_decimals': 5,
+//                          'fee_volume_currency': 'ZUSD',
+//                          'fees': [[0, 0.26],
+//                                   [50000, 0.24],
+//                                   [100000, 0.22],
+//                                   [250000, 0.2],
+//                                   [500000, 0.18],
+//                                   [1000000, 0.16],
+//                                   [2500000, 0.14],
+//                                   [5000000, 0.12],
+//                                   [10000000, 0.1]],
+//                          'fees_maker': [[0, 0.16],
+//                                         [50000, 0.14],
+//                                         [100000, 0.12],
+//                                         [250000, 0.1],
+//                                         [500000, 0.08],
+//                                         [1000000, 0.06],
+//                                         [2500000, 0.04],
+//                                         [5000000, 0.02],
+//                                         [10000000, 0.0]],
+//                          'leverage_buy': [2, 3, 4, 5],
+//                          'leverage_sell': [2, 3, 4, 5],
+//                          'lot': 'unit',
+//                          'lot_decimals': 8,
+//                          'lot_multiplier': 1,
+//                          'margin_call': 80,
+//                          'margin_stop': 40,
+//                          'ordermin': '0.0001',
+//                          'pair_decimals': 1,
+//                          'quote': 'ZUSD',
+//                          'wsname': 'XBT/USD'}}}
+
+#[derive(Debug, Deserialize)]
+pub struct SystemStatus {
+    pub status: String,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssetInfo {
+    pub aclass: String,
+    pub altname: String,
+    pub decimals: u8,
+    pub display_decimals: u8,
+    pub collateral_value: Option<f32>,
+    pub status: Option<String>,
+}
+
+// Values are [price, whole lot volume, lot volume].
+#[derive(Debug, Deserialize)]
+pub struct TickerPrice(pub Decimal, pub Decimal, pub Decimal);
+
+#[derive(Debug, Deserialize)]
+pub struct TickerInfo {
+    pub a: TickerPrice,
+    pub b: TickerPrice,
+    pub c: (Decimal, Decimal),
+    pub v: (Decimal, Decimal),
+    pub p: (Decimal, Decimal),
+    pub t: (u64, u64),
+    pub l: (Decimal, Decimal),
+    pub h: (Decimal, Decimal),
+    pub o: Decimal,
+}
+
+// Values are [time, open, high, low, close, vwap, volume, count].
+#[derive(Debug, Deserialize)]
+pub struct OhlcEntry(
+    pub i64,
+    pub Decimal,
+    pub Decimal,
+    pub Decimal,
+    pub Decimal,
+    pub Decimal,
+    pub Decimal,
+    pub u64,
+);
+
+#[derive(Debug, Deserialize)]
+pub struct OhlcResult {
+    pub last: i64,
+    #[serde(flatten)]
+    pub pairs: HashMap<String, Vec<OhlcEntry>>,
+}
+
+// Values are [price, volume, timestamp].
+#[derive(Debug, Deserialize)]
+pub struct BookEntry(pub Decimal, pub Decimal, pub i64);
+
+#[derive(Debug, Deserialize)]
+pub struct OrderBook {
+    pub asks: Vec<BookEntry>,
+    pub bids: Vec<BookEntry>,
+}
+
+// Values are [price, volume, time, buy/sell, market/limit, miscellaneous, trade id].
+#[derive(Debug, Deserialize)]
+pub struct TradeEntry(
+    pub Decimal,
+    pub Decimal,
+    pub Decimal,
+    pub String,
+    pub String,
+    pub String,
+    pub u64,
+);
+
+#[derive(Debug, Deserialize)]
+pub struct TradesResult {
+    pub last: String,
+    #[serde(flatten)]
+    pub pairs: HashMap<String, Vec<TradeEntry>>,
+}
+
+// Values are [time, bid, ask].
+#[derive(Debug, Deserialize)]
+pub struct SpreadEntry(pub i64, pub Decimal, pub Decimal);
+
+#[derive(Debug, Deserialize)]
+pub struct SpreadResult {
+    pub last: i64,
+    #[serde(flatten)]
+    pub pairs: HashMap<String, Vec<SpreadEntry>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrderDetails {
+    pub cost: Decimal,
+    pub descr: HashMap<String, String>,
+    pub expiretm: u32,
+    pub fee: Decimal,
+    pub limitprice: Decimal,
+    pub misc: String,
+    pub oflags: String,
+    pub opentm: Decimal,
+    pub price: Decimal,
+    pub refid: Option<String>,
+    pub starttm: u32,
+    pub status: String,
+    pub stopprice: Decimal,
+    pub userref: u32,
+    pub vol: Decimal,
+    pub vol_exec: Decimal,
+}
+
+// NOTE: Example of OpenOrders response
+// {'error': [],
+//  'result': {'open': {'O3TYXG-PZKBP-FVQGOK': {'cost': '0.000000',
+//                                              'descr': {'close': '',
+//                                                        'leverage': 'none',
+//                                                        'order': 'sell '
+//                                                                 '0.01100000 '
+//                                                                 'ETHXBT @ '
+//                                                                 'limit 0.50000',
+//                                                        'ordertype': 'limit',
+//                                                        'pair': 'ETHXBT',
+//                                                        'price': '0.50000',
+//                                                        'price2': '0',
+//                                                        'type': 'sell'},
+//                                              'expiretm': 0,
+//                                              'fee': '0.000000',
+//                                              'limitprice': '0.000000',
+//                                              'misc': '',
+//                                              'oflags': 'fciq',
+//                                              'opentm': 1665312858.4293625,
+//                                              'price': '0.000000',
+//                                              'refid': None,
+//                                              'starttm': 0,
+//                                              'status': 'open',
+//                                              'stopprice': '0.000000',
+//                                              'userref': 0,
+//                                              'vol': '0.01100000',
+//                                              'vol_exec': '0.00000000'}}}}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenOrders {
+    pub open: HashMap<String, OrderDetails>,
+}
+
+/// Order returned by ClosedOrders and QueryOrders, the later one returns also open orders.
+#[derive(Debug, Deserialize)]
+pub struct OrderInfo {
+    #[serde(flatten)]
+    pub details: OrderDetails,
+    pub closetm: Option<Decimal>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClosedOrders {
+    pub closed: HashMap<String, OrderInfo>,
+    pub count: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrderDescription {
+    pub order: String,
+    pub close: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddOrderResult {
+    pub descr: OrderDescription,
+    // Missing when order is only validated
+    pub txid: Option<Vec<String>>,
+}
+
+/// Result of one order in the batch, in the same position as the order was sent.
+#[derive(Debug, Deserialize)]
+pub struct BatchOrderResult {
+    pub txid: Option<String>,
+    pub descr: Option<OrderDescription>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddOrderBatchResult {
+    pub orders: Vec<BatchOrderResult>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EditOrderResult {
+    pub descr: OrderDescription,
+    pub txid: Option<String>,
+    pub originaltxid: Option<String>,
+    pub volume: Decimal,
+    pub price: Decimal,
+    pub price2: Option<Decimal>,
+    pub orders_cancelled: Option<u32>,
+    pub status: String,
+    pub error_message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AmendOrderResult {
+    pub amend_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CancelOrderResult {
+    pub count: u32,
+    pub pending: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelAllOrdersAfterResult {
+    pub current_time: String,
+    // "0" when the switch is disarmed
+    pub trigger_time: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExtendedBalance {
+    pub balance: Decimal,
+    pub hold_trade: Decimal,
+    pub credit: Option<Decimal>,
+    pub credit_used: Option<Decimal>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TradeBalance {
+    pub eb: Decimal,
+    pub tb: Decimal,
+    pub m: Decimal,
+    pub n: Decimal,
+    pub c: Decimal,
+    pub v: Decimal,
+    pub e: Decimal,
+    pub mf: Decimal,
+    pub ml: Option<Decimal>,
+    pub uv: Option<Decimal>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TradeInfo {
+    pub ordertxid: String,
+    pub postxid: String,
+    pub pair: String,
+    pub time: Decimal,
+    #[serde(rename = "type")]
+    pub side: String,
+    pub ordertype: String,
+    pub price: Decimal,
+    pub cost: Decimal,
+    pub fee: Decimal,
+    pub vol: Decimal,
+    pub margin: Decimal,
+    pub misc: String,
+    pub leverage: Option<String>,
+    pub trade_id: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TradesHistory {
+    pub trades: HashMap<String, TradeInfo>,
+    pub count: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PositionInfo {
+    pub ordertxid: String,
+    pub posstatus: String,
+    pub pair: String,
+    pub time: Decimal,
+    #[serde(rename = "type")]
+    pub side: String,
+    pub ordertype: String,
+    pub cost: Decimal,
+    pub fee: Decimal,
+    pub vol: Decimal,
+    pub vol_closed: Decimal,
+    pub margin: Decimal,
+    pub terms: String,
+    pub rollovertm: String,
+    pub misc: String,
+    pub oflags: String,
+    // Only with docalcs=true
+    pub value: Option<Decimal>,
+    pub net: Option<Decimal>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LedgerEntry {
+    pub refid: String,
+    pub time: Decimal,
+    #[serde(rename = "type")]
+    pub entry_type: String,
+    pub subtype: String,
+    pub aclass: String,
+    pub asset: String,
+    pub amount: Decimal,
+    pub fee: Decimal,
+    pub balance: Decimal,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Ledgers {
+    pub ledger: HashMap<String, LedgerEntry>,
+    pub count: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeeTierInfo {
+    pub fee: Decimal,
+    pub minfee: Decimal,
+    pub maxfee: Decimal,
+    pub nextfee: Option<Decimal>,
+    pub nextvolume: Option<Decimal>,
+    pub tiervolume: Option<Decimal>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TradeVolume {
+    pub currency: String,
+    pub volume: Decimal,
+    // Only when pair is requested
+    pub fees: Option<HashMap<String, FeeTierInfo>>,
+    pub fees_maker: Option<HashMap<String, FeeTierInfo>>,
+}
@@ -0,0 +1,625 @@
+//! Typed parameters of AddOrder request.
+
+use std::{fmt, str::FromStr};
+
+use rust_decimal::Decimal;
+
+use crate::{clock::Clock, models::TradingPairAll};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Side::Buy => "buy",
+            Side::Sell => "sell",
+        }
+    }
+}
+
+/// Order type with its prices. Prices are sent as they are, so they can be relative, e.g. "+10" or "2%".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderType {
+    Market,
+    Limit {
+        price: String,
+    },
+    /// Limit order, which shows only `display_volume` in the book.
+    Iceberg {
+        price: String,
+        display_volume: String,
+    },
+    StopLoss {
+        trigger: String,
+    },
+    TakeProfit {
+        trigger: String,
+    },
+    StopLossLimit {
+        trigger: String,
+        limit: String,
+    },
+    TakeProfitLimit {
+        trigger: String,
+        limit: String,
+    },
+    /// Offsets are relative to the market price, e.g. "+50".
+    TrailingStop {
+        offset: String,
+    },
+    TrailingStopLimit {
+        offset: String,
+        limit_offset: String,
+    },
+    SettlePosition,
+}
+
+impl OrderType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            OrderType::Market => "market",
+            OrderType::Limit { .. } => "limit",
+            OrderType::Iceberg { .. } => "iceberg",
+            OrderType::StopLoss { .. } => "stop-loss",
+            OrderType::TakeProfit { .. } => "take-profit",
+            OrderType::StopLossLimit { .. } => "stop-loss-limit",
+            OrderType::TakeProfitLimit { .. } => "take-profit-limit",
+            OrderType::TrailingStop { .. } => "trailing-stop",
+            OrderType::TrailingStopLimit { .. } => "trailing-stop-limit",
+            OrderType::SettlePosition => "settle-position",
+        }
+    }
+
+    /// Values of price and price2 params.
+    fn prices(&self) -> (Option<&str>, Option<&str>) {
+        match self {
+            OrderType::Market | OrderType::SettlePosition => (None, None),
+            OrderType::Limit { price } | OrderType::Iceberg { price, .. } => (Some(price), None),
+            OrderType::StopLoss { trigger } | OrderType::TakeProfit { trigger } => {
+                (Some(trigger), None)
+            }
+            OrderType::StopLossLimit { trigger, limit }
+            | OrderType::TakeProfitLimit { trigger, limit } => (Some(trigger), Some(limit)),
+            OrderType::TrailingStop { offset } => (Some(offset), None),
+            OrderType::TrailingStopLimit {
+                offset,
+                limit_offset,
+            } => (Some(offset), Some(limit_offset)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderFlag {
+    /// Post-only, the order is cancelled instead of taking liquidity.
+    Post,
+    /// Fee in base currency.
+    Fcib,
+    /// Fee in quote currency.
+    Fciq,
+    /// No market price protection.
+    Nompp,
+    /// Volume in quote currency.
+    Viqc,
+}
+
+impl OrderFlag {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderFlag::Post => "post",
+            OrderFlag::Fcib => "fcib",
+            OrderFlag::Fciq => "fciq",
+            OrderFlag::Nompp => "nompp",
+            OrderFlag::Viqc => "viqc",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeInForce {
+    GoodTillCancelled,
+    ImmediateOrCancel,
+    /// Needs expire time.
+    GoodTillDate,
+}
+
+impl TimeInForce {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeInForce::GoodTillCancelled => "GTC",
+            TimeInForce::ImmediateOrCancel => "IOC",
+            TimeInForce::GoodTillDate => "GTD",
+        }
+    }
+}
+
+/// Start or expire time of the order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderTime {
+    Now,
+    /// Seconds from now.
+    After(u64),
+    /// Unix timestamp.
+    At(i64),
+}
+
+impl OrderTime {
+    /// Unix timestamp given seconds after current time of the clock. With `SynchronizedClock` it is server time, so
+    /// it holds even when the local clock drifts.
+    pub fn at_server_time(clock: &dyn Clock, after_secs: i64) -> OrderTime {
+        OrderTime::At(clock.now_ms() / 1000 + after_secs)
+    }
+}
+
+impl fmt::Display for OrderTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderTime::Now => write!(f, "0"),
+            OrderTime::After(secs) => write!(f, "+{secs}"),
+            OrderTime::At(timestamp) => write!(f, "{timestamp}"),
+        }
+    }
+}
+
+/// Error of parsing order parameter from text, e.g. unknown order flag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOrderError(pub String);
+
+impl fmt::Display for ParseOrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ParseOrderError {}
+
+impl FromStr for Side {
+    type Err = ParseOrderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "buy" => Ok(Side::Buy),
+            "sell" => Ok(Side::Sell),
+            _ => Err(ParseOrderError(format!("unknown side {s}"))),
+        }
+    }
+}
+
+impl FromStr for OrderFlag {
+    type Err = ParseOrderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "post" => Ok(OrderFlag::Post),
+            "fcib" => Ok(OrderFlag::Fcib),
+            "fciq" => Ok(OrderFlag::Fciq),
+            "nompp" => Ok(OrderFlag::Nompp),
+            "viqc" => Ok(OrderFlag::Viqc),
+            _ => Err(ParseOrderError(format!("unknown order flag {s}"))),
+        }
+    }
+}
+
+impl FromStr for TimeInForce {
+    type Err = ParseOrderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "GTC" => Ok(TimeInForce::GoodTillCancelled),
+            "IOC" => Ok(TimeInForce::ImmediateOrCancel),
+            "GTD" => Ok(TimeInForce::GoodTillDate),
+            _ => Err(ParseOrderError(format!("unknown time in force {s}"))),
+        }
+    }
+}
+
+impl FromStr for OrderTime {
+    type Err = ParseOrderError;
+
+    /// Accepts the same format as the API: "0", "+<seconds>" or "<unix timestamp>".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseOrderError(format!("invalid order time {s}"));
+        match s {
+            "0" => Ok(OrderTime::Now),
+            _ => match s.strip_prefix('+') {
+                Some(secs) => secs.parse().map(OrderTime::After).map_err(|_| invalid()),
+                None => s.parse().map(OrderTime::At).map_err(|_| invalid()),
+            },
+        }
+    }
+}
+
+/// AddOrder request, e.g.
+/// `OrderRequest::new(Side::Sell, "ETHXBT", "0.011", OrderType::Limit { price: "0.5".to_string() }).userref(42)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderRequest {
+    pub side: Side,
+    pub pair: String,
+    pub volume: String,
+    pub order_type: OrderType,
+    pub oflags: Vec<OrderFlag>,
+    pub time_in_force: Option<TimeInForce>,
+    pub start: Option<OrderTime>,
+    pub expire: Option<OrderTime>,
+    pub leverage: Option<u8>,
+    pub reduce_only: bool,
+    pub userref: Option<i32>,
+    pub cl_ord_id: Option<String>,
+    /// Conditional order, which closes the position once this order is filled.
+    pub close: Option<OrderType>,
+    pub validate: bool,
+}
+
+impl OrderRequest {
+    pub fn new(side: Side, pair: &str, volume: &str, order_type: OrderType) -> OrderRequest {
+        OrderRequest {
+            side,
+            pair: pair.to_string(),
+            volume: volume.to_string(),
+            order_type,
+            oflags: Vec::new(),
+            time_in_force: None,
+            start: None,
+            expire: None,
+            leverage: None,
+            reduce_only: false,
+            userref: None,
+            cl_ord_id: None,
+            close: None,
+            validate: false,
+        }
+    }
+
+    pub fn oflag(mut self, flag: OrderFlag) -> Self {
+        self.oflags.push(flag);
+        self
+    }
+
+    pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.time_in_force = Some(time_in_force);
+        self
+    }
+
+    pub fn start(mut self, start: OrderTime) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    pub fn expire(mut self, expire: OrderTime) -> Self {
+        self.expire = Some(expire);
+        self
+    }
+
+    pub fn leverage(mut self, leverage: u8) -> Self {
+        self.leverage = Some(leverage);
+        self
+    }
+
+    pub fn reduce_only(mut self) -> Self {
+        self.reduce_only = true;
+        self
+    }
+
+    pub fn userref(mut self, userref: i32) -> Self {
+        self.userref = Some(userref);
+        self
+    }
+
+    pub fn cl_ord_id(mut self, cl_ord_id: &str) -> Self {
+        self.cl_ord_id = Some(cl_ord_id.to_string());
+        self
+    }
+
+    pub fn close(mut self, close: OrderType) -> Self {
+        self.close = Some(close);
+        self
+    }
+
+    /// Order is only validated by the server, it is not placed.
+    pub fn validate(mut self) -> Self {
+        self.validate = true;
+        self
+    }
+
+    /// Request params in the order, in which they are sent.
+    pub fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![
+            ("ordertype", self.order_type.name().to_string()),
+            ("type", self.side.as_str().to_string()),
+            ("volume", self.volume.clone()),
+            ("pair", self.pair.clone()),
+        ];
+        let (price, price2) = self.order_type.prices();
+        if let Some(price) = price {
+            params.push(("price", price.to_string()));
+        }
+        if let Some(price2) = price2 {
+            params.push(("price2", price2.to_string()));
+        }
+        if let OrderType::Iceberg { display_volume, .. } = &self.order_type {
+            params.push(("displayvol", display_volume.clone()));
+        }
+        if !self.oflags.is_empty() {
+            let oflags: Vec<&str> = self.oflags.iter().map(OrderFlag::as_str).collect();
+            params.push(("oflags", oflags.join(",")));
+        }
+        if let Some(time_in_force) = self.time_in_force {
+            params.push(("timeinforce", time_in_force.as_str().to_string()));
+        }
+        if let Some(start) = self.start {
+            params.push(("starttm", start.to_string()));
+        }
+        if let Some(expire) = self.expire {
+            params.push(("expiretm", expire.to_string()));
+        }
+        if let Some(leverage) = self.leverage {
+            params.push(("leverage", leverage.to_string()));
+        }
+        if self.reduce_only {
+            params.push(("reduce_only", "true".to_string()));
+        }
+        if let Some(userref) = self.userref {
+            params.push(("userref", userref.to_string()));
+        }
+        if let Some(cl_ord_id) = &self.cl_ord_id {
+            params.push(("cl_ord_id", cl_ord_id.clone()));
+        }
+        if let Some(close) = &self.close {
+            params.push(("close[ordertype]", close.name().to_string()));
+            let (price, price2) = close.prices();
+            if let Some(price) = price {
+                params.push(("close[price]", price.to_string()));
+            }
+            if let Some(price2) = price2 {
+                params.push(("close[price2]", price2.to_string()));
+            }
+        }
+        if self.validate {
+            params.push(("validate", "true".to_string()));
+        }
+        params
+    }
+}
+
+/// Constraint of the asset pair, which the order breaks. Checked by [`OrderRequest::check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstraintError {
+    /// Volume or price is not a number.
+    InvalidNumber(String),
+    VolumeBelowMinimum {
+        volume: Decimal,
+        ordermin: Decimal,
+    },
+    VolumePrecision {
+        volume: Decimal,
+        lot_decimals: u8,
+    },
+    PricePrecision {
+        price: Decimal,
+        pair_decimals: u8,
+    },
+    TickSize {
+        price: Decimal,
+        tick_size: Decimal,
+    },
+    CostBelowMinimum {
+        cost: Decimal,
+        costmin: Decimal,
+    },
+}
+
+impl ConstraintError {
+    /// Name of the violated AssetPairs field.
+    pub fn rule(&self) -> &'static str {
+        match self {
+            ConstraintError::InvalidNumber(_) => "number",
+            ConstraintError::VolumeBelowMinimum { .. } => "ordermin",
+            ConstraintError::VolumePrecision { .. } => "lot_decimals",
+            ConstraintError::PricePrecision { .. } => "pair_decimals",
+            ConstraintError::TickSize { .. } => "tick_size",
+            ConstraintError::CostBelowMinimum { .. } => "costmin",
+        }
+    }
+}
+
+impl fmt::Display for ConstraintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstraintError::InvalidNumber(val) => write!(f, "{val} is not a number"),
+            ConstraintError::VolumeBelowMinimum { volume, ordermin } => {
+                write!(f, "volume {volume} is lower than ordermin {ordermin}")
+            }
+            ConstraintError::VolumePrecision {
+                volume,
+                lot_decimals,
+            } => write!(f, "volume {volume} has more than {lot_decimals} decimals"),
+            ConstraintError::PricePrecision {
+                price,
+                pair_decimals,
+            } => write!(f, "price {price} has more than {pair_decimals} decimals"),
+            ConstraintError::TickSize { price, tick_size } => {
+                write!(
+                    f,
+                    "price {price} is not a multiple of tick size {tick_size}"
+                )
+            }
+            ConstraintError::CostBelowMinimum { cost, costmin } => {
+                write!(f, "cost {cost} is lower than costmin {costmin}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConstraintError {}
+
+fn parse_number(val: &str) -> Result<Decimal, ConstraintError> {
+    val.parse()
+        .map_err(|_| ConstraintError::InvalidNumber(val.to_string()))
+}
+
+/// Number of significant decimals, trailing zeros are not counted.
+fn decimals(val: Decimal) -> u32 {
+    val.normalize().scale()
+}
+
+/// Absolute price, relative ones (e.g. "+10", "#5", "2%") depend on the market and are not checked.
+fn absolute_price(price: &str) -> Option<&str> {
+    let relative = price.starts_with(['+', '-', '#']) || price.ends_with('%');
+    (!relative).then_some(price)
+}
+
+impl OrderRequest {
+    /// Checks the order against constraints of its pair from AssetPairs, the same way as the exchange does.
+    /// Cost is checked against costmin only when it is known: from the limit price of limit, iceberg, stop-loss-limit
+    /// and take-profit-limit orders, or from volume in quote currency.
+    pub fn check(&self, pair: &TradingPairAll) -> Result<(), ConstraintError> {
+        let volume = parse_number(&self.volume)?;
+        // With viqc volume is in quote currency, while ordermin and lot_decimals are of base currency.
+        let in_quote = self.oflags.contains(&OrderFlag::Viqc);
+        if !in_quote && volume < pair.ordermin {
+            return Err(ConstraintError::VolumeBelowMinimum {
+                volume,
+                ordermin: pair.ordermin,
+            });
+        }
+        if !in_quote && decimals(volume) > pair.lot_decimals.into() {
+            return Err(ConstraintError::VolumePrecision {
+                volume,
+                lot_decimals: pair.lot_decimals,
+            });
+        }
+        let (price, price2) = self.order_type.prices();
+        let prices = [price, price2]
+            .into_iter()
+            .flatten()
+            .filter_map(absolute_price);
+        for price in prices {
+            let price = parse_number(price)?;
+            if decimals(price) > pair.pair_decimals.into() {
+                return Err(ConstraintError::PricePrecision {
+                    price,
+                    pair_decimals: pair.pair_decimals,
+                });
+            }
+            // Zero tick size means there is none.
+            if let Some(tick_size) = pair.tick_size.filter(|tick_size| !tick_size.is_zero()) {
+                if !(price % tick_size).is_zero() {
+                    return Err(ConstraintError::TickSize { price, tick_size });
+                }
+            }
+        }
+        // Cost is known only for orders with a limit price, or volume given in quote currency.
+        let cost = if in_quote {
+            Some(volume)
+        } else {
+            match &self.order_type {
+                OrderType::Limit { price }
+                | OrderType::Iceberg { price, .. }
+                | OrderType::StopLossLimit { limit: price, .. }
+                | OrderType::TakeProfitLimit { limit: price, .. } => absolute_price(price)
+                    .map(parse_number)
+                    .transpose()?
+                    .map(|price| price * volume),
+                _ => None,
+            }
+        };
+        if let (Some(cost), Some(costmin)) = (cost, pair.costmin) {
+            if cost < costmin {
+                return Err(ConstraintError::CostBelowMinimum {
+                    cost: cost.round_dp(pair.cost_decimals),
+                    costmin,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::clock::FakeClock;
+
+    fn pair(name: &str) -> TradingPairAll {
+        let mut pairs: HashMap<String, TradingPairAll> =
+            serde_json::from_str(include_str!("../tests/fixtures/public/asset_pairs.json"))
+                .expect("AssetPairs fixture is valid");
+        pairs.remove(name).expect("Pair is in the fixture")
+    }
+
+    fn limit(volume: &str, price: &str) -> OrderRequest {
+        OrderRequest::new(
+            Side::Buy,
+            "XBTUSD",
+            volume,
+            OrderType::Limit {
+                price: price.to_string(),
+            },
+        )
+    }
+
+    #[test]
+    fn zero_tick_size_is_not_checked() {
+        let mut pair = pair("XXBTZUSD");
+        pair.tick_size = Some(Decimal::ZERO);
+        assert_eq!(limit("0.01", "100.1").check(&pair), Ok(()));
+    }
+
+    #[test]
+    fn price_off_the_tick_size_is_rejected() {
+        let mut pair = pair("XXBTZUSD");
+        pair.tick_size = Some(Decimal::new(5, 1));
+        let rule = limit("0.01", "100.1").check(&pair).map_err(|e| e.rule());
+        assert_eq!(rule, Err("tick_size"));
+    }
+
+    #[test]
+    fn volume_in_quote_currency_is_not_compared_with_ordermin() {
+        let pair = pair("XXBTZUSD");
+        // 0.00005 XBT is below ordermin, but 50 USD is a valid cost.
+        assert!(limit("0.00005", "100").check(&pair).is_err());
+        assert_eq!(
+            limit("50", "100").oflag(OrderFlag::Viqc).check(&pair),
+            Ok(())
+        );
+        let rule = limit("0.1", "100")
+            .oflag(OrderFlag::Viqc)
+            .check(&pair)
+            .map_err(|e| e.rule());
+        assert_eq!(rule, Err("costmin"));
+    }
+
+    #[test]
+    fn cost_of_stop_limit_orders_is_checked_at_limit_price() {
+        let pair = pair("XXBTZUSD");
+        let order = |order_type| OrderRequest::new(Side::Sell, "XBTUSD", "0.0001", order_type);
+        let stop_loss = order(OrderType::StopLossLimit {
+            trigger: "100000".to_string(),
+            limit: "100".to_string(),
+        });
+        assert_eq!(stop_loss.check(&pair).map_err(|e| e.rule()), Err("costmin"));
+        let take_profit = order(OrderType::TakeProfitLimit {
+            trigger: "100000".to_string(),
+            limit: "100".to_string(),
+        });
+        assert_eq!(
+            take_profit.check(&pair).map_err(|e| e.rule()),
+            Err("costmin")
+        );
+    }
+
+    #[test]
+    fn order_time_is_read_from_the_clock() {
+        let clock = FakeClock::stopped_at(1_700_000_000_900);
+        assert_eq!(
+            OrderTime::at_server_time(&clock, 60),
+            OrderTime::At(1_700_000_060)
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use serde_json::Value;
+
+use crate::OutputFormat;
+
+/// Prints result of the response in selected format and errors to stderr.
+/// Returns false when server responded with errors.
+pub fn print_response(text: &str, format: OutputFormat) -> bool {
+    let body: Value = match serde_json::from_str(text) {
+        Ok(body) => body,
+        Err(_) => {
+            eprintln!("Response is not a JSON document:\n{text}");
+            return false;
+        }
+    };
+    let errors: Vec<&str> = body["error"]
+        .as_array()
+        .map(|errors| errors.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    for error in &errors {
+        eprintln!("error: {error}");
+    }
+    let result = &body["result"];
+    if !result.is_null() {
+        match format {
+            OutputFormat::Json => println!(
+                "{}",
+                serde_json::to_string_pretty(result).expect("Value is always serializable")
+            ),
+            OutputFormat::Table => print!("{}", render_table(result)),
+        }
+    }
+    errors.is_empty()
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Object, where every value is an object too, e.g. pairs by name or orders by txid.
+fn as_collection(value: &Value) -> Option<&serde_json::Map<String, Value>> {
+    value
+        .as_object()
+        .filter(|map| !map.is_empty() && map.values().all(Value::is_object))
+}
+
+/// Collections are printed one row per entry, anything else as key/value rows.
+/// Wrapper objects with a single collection inside, like {"open": {...}}, are unwrapped.
+pub fn render_table(value: &Value) -> String {
+    let mut value = value;
+    while let Some(inner) = value
+        .as_object()
+        .filter(|map| map.len() == 1)
+        .and_then(|map| map.values().next())
+        .filter(|inner| as_collection(inner).is_some())
+    {
+        value = inner;
+    }
+
+    let rows: Vec<Vec<String>> = match (as_collection(value), value) {
+        (Some(collection), _) if collection.len() > 1 => {
+            let mut columns: Vec<&String> = Vec::new();
+            for entry in collection.values().filter_map(Value::as_object) {
+                for key in entry.keys() {
+                    if !columns.contains(&key) {
+                        columns.push(key);
+                    }
+                }
+            }
+            let header = std::iter::once("key".to_string())
+                .chain(columns.iter().map(|column| column.to_string()))
+                .collect();
+            let entries = collection.iter().map(|(name, entry)| {
+                std::iter::once(name.clone())
+                    .chain(columns.iter().map(|column| cell(&entry[column.as_str()])))
+                    .collect()
+            });
+            std::iter::once(header).chain(entries).collect()
+        }
+        (Some(collection), _) => {
+            let (name, entry) = collection.iter().next().expect("collection is not empty");
+            let fields = entry.as_object().expect("collection entries are objects");
+            std::iter::once(vec!["key".to_string(), name.clone()])
+                .chain(fields.iter().map(|(key, val)| vec![key.clone(), cell(val)]))
+                .collect()
+        }
+        (None, Value::Object(map)) => map
+            .iter()
+            .map(|(key, val)| vec![key.clone(), cell(val)])
+            .collect(),
+        (None, Value::Array(items)) => items
+            .iter()
+            .enumerate()
+            .map(|(i, val)| vec![i.to_string(), cell(val)])
+            .collect(),
+        (None, other) => vec![vec![cell(other)]],
+    };
+
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|i| {
+            rows.iter()
+                .filter_map(|row| row.get(i))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let mut out = String::new();
+    for row in &rows {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        out.push_str(line.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,180 @@
+//! Preflight of API key permissions. Each one is probed by a call, which changes nothing on the account.
+
+use std::{fmt, str::FromStr};
+
+use crate::{
+    client::{private_call, NonceGen, SquidCreds},
+    models::CriticalErrorResponse,
+};
+
+/// Error of calls, which the API key has no permission for.
+pub const PERMISSION_DENIED: &str = "EGeneral:Permission denied";
+
+/// Permission of API key, which can be probed. Cancelling orders cannot be probed without risk, so it is not here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Permission {
+    QueryFunds,
+    QueryOpenOrders,
+    QueryClosedOrders,
+    QueryLedger,
+    ModifyOrders,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 5] = [
+        Permission::QueryFunds,
+        Permission::QueryOpenOrders,
+        Permission::QueryClosedOrders,
+        Permission::QueryLedger,
+        Permission::ModifyOrders,
+    ];
+
+    /// Name used in scenario tags, e.g. @permission-query-funds.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Permission::QueryFunds => "query-funds",
+            Permission::QueryOpenOrders => "query-open-orders",
+            Permission::QueryClosedOrders => "query-closed-orders",
+            Permission::QueryLedger => "query-ledger",
+            Permission::ModifyOrders => "modify-orders",
+        }
+    }
+
+    /// Call, which needs only this permission. Order is only validated, so it is never placed.
+    fn probe(&self) -> (&'static str, &'static [(&'static str, &'static str)]) {
+        match self {
+            Permission::QueryFunds => ("Balance", &[]),
+            Permission::QueryOpenOrders => ("OpenOrders", &[]),
+            Permission::QueryClosedOrders => ("ClosedOrders", &[]),
+            Permission::QueryLedger => ("Ledgers", &[]),
+            Permission::ModifyOrders => (
+                "AddOrder",
+                &[
+                    ("pair", "ETHXBT"),
+                    ("type", "sell"),
+                    ("ordertype", "limit"),
+                    ("price", "0.5"),
+                    ("volume", "0.011"),
+                    ("validate", "true"),
+                ],
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Permission {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Permission::ALL
+            .into_iter()
+            .find(|permission| permission.name() == s)
+            .ok_or_else(|| format!("unknown permission {s}"))
+    }
+}
+
+/// Result of the probe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Access {
+    Granted,
+    Denied,
+    /// Probe failed for other reason, e.g. invalid key or network error.
+    Unknown(String),
+}
+
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Access::Granted => write!(f, "granted"),
+            Access::Denied => write!(f, "denied"),
+            Access::Unknown(reason) => write!(f, "unknown ({reason})"),
+        }
+    }
+}
+
+/// Access by errors of the response. Only permission is checked, other errors (e.g. insufficient funds) mean it is granted.
+pub fn classify(response_text: &str) -> Access {
+    let Ok(body) = serde_json::from_str::<CriticalErrorResponse>(response_text) else {
+        return Access::Unknown("malformed response".to_string());
+    };
+    if body
+        .error
+        .iter()
+        .any(|error| error.starts_with(PERMISSION_DENIED))
+    {
+        Access::Denied
+    } else if let Some(error) = body.error.iter().find(|error| error.starts_with("EAPI:")) {
+        Access::Unknown(error.clone())
+    } else {
+        Access::Granted
+    }
+}
+
+/// Probes every permission of the key.
+pub async fn probe(creds: &SquidCreds, nonce_gen: &mut NonceGen) -> Vec<(Permission, Access)> {
+    let mut permissions = Vec::new();
+    for permission in Permission::ALL {
+        let (endpoint, params) = permission.probe();
+        let access = match private_call(endpoint, params, creds, nonce_gen).await {
+            Ok(response) => classify(&response.text),
+            Err(e) => Access::Unknown(e.to_string()),
+        };
+        permissions.push((permission, access));
+    }
+    permissions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permission_denied_is_denied() {
+        assert_eq!(
+            classify(r#"{"error":["EGeneral:Permission denied"]}"#),
+            Access::Denied
+        );
+    }
+
+    #[test]
+    fn key_and_nonce_errors_are_unknown() {
+        assert_eq!(
+            classify(r#"{"error":["EAPI:Invalid key"]}"#),
+            Access::Unknown("EAPI:Invalid key".to_string())
+        );
+        assert_eq!(
+            classify(r#"{"error":["EAPI:Invalid nonce"]}"#),
+            Access::Unknown("EAPI:Invalid nonce".to_string())
+        );
+    }
+
+    #[test]
+    fn order_errors_are_granted() {
+        assert_eq!(
+            classify(r#"{"error":["EOrder:Insufficient funds"]}"#),
+            Access::Granted
+        );
+    }
+
+    #[test]
+    fn result_is_granted() {
+        assert_eq!(
+            classify(r#"{"error":[],"result":{"ZUSD":"171.2880"}}"#),
+            Access::Granted
+        );
+    }
+
+    #[test]
+    fn malformed_response_is_unknown() {
+        assert_eq!(
+            classify("<html>Bad gateway</html>"),
+            Access::Unknown("malformed response".to_string())
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! Named environments (production, mock or custom) read from environments.toml.
+
+use std::{collections::HashMap, fmt, path::PathBuf};
+
+use serde::Deserialize;
+
+use crate::client::SquidCreds;
+
+/// Name of the selected environment, when it is not given with --env flag.
+pub const ENV_NAME_ENV: &str = "SQUID_ENV";
+/// Path of the environments file, environments.toml in the current directory by default.
+pub const ENV_FILE_ENV: &str = "SQUID_ENV_FILE";
+
+const DEFAULT_ENV_FILE: &str = "environments.toml";
+const DEFAULT_MAX_CLOCK_SKEW_SECS: i64 = 5;
+
+/// One environment of the environments file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Profile {
+    #[serde(skip)]
+    pub name: String,
+    pub rest_url: Option<String>,
+    pub ws_public_url: Option<String>,
+    pub ws_private_url: Option<String>,
+    /// Local fake exchange provides URL and credentials.
+    #[serde(default)]
+    pub fake_exchange: bool,
+    /// False only for APIs, which cannot reach a live account, e.g. a sandbox. Orders need no SQUID_LIVE_TRADING then.
+    #[serde(default = "default_live")]
+    pub live: bool,
+    /// Shift of the fake exchange clock from the system clock.
+    #[serde(default)]
+    pub fake_clock_skew_ms: i64,
+    #[serde(default)]
+    pub credentials: CredentialProfile,
+    #[serde(default = "default_max_clock_skew_secs")]
+    pub max_clock_skew_secs: i64,
+    /// Expected AssetPairs data by pair name, e.g. XXBTZUSD.
+    #[serde(default)]
+    pub reference: HashMap<String, PairReference>,
+}
+
+fn default_live() -> bool {
+    true
+}
+
+fn default_max_clock_skew_secs() -> i64 {
+    DEFAULT_MAX_CLOCK_SKEW_SECS
+}
+
+/// Env variables with API key and secret of the environment.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CredentialProfile {
+    pub key_env: String,
+    pub secret_env: String,
+}
+
+impl Default for CredentialProfile {
+    fn default() -> Self {
+        CredentialProfile {
+            key_env: crate::client::API_KEY_ENV.to_string(),
+            secret_env: crate::client::API_SEC_ENV.to_string(),
+        }
+    }
+}
+
+impl CredentialProfile {
+    /// Credentials from the env variables, None when any of them is not set.
+    pub fn load(&self) -> Option<SquidCreds> {
+        Some(SquidCreds {
+            key: std::env::var(&self.key_env).ok()?,
+            secret: std::env::var(&self.secret_env).ok()?,
+            key_env: self.key_env.clone(),
+        })
+    }
+}
+
+/// Reference data of the pair, which does not change in time.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PairReference {
+    pub aclass_base: String,
+    pub aclass_quote: String,
+    pub altname: String,
+    pub base: String,
+    pub fee_volume_currency: String,
+    pub lot: String,
+    pub quote: String,
+    pub wsname: String,
+}
+
+#[derive(Debug)]
+pub enum ProfileError {
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    Unknown {
+        name: String,
+        path: PathBuf,
+        known: Vec<String>,
+    },
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileError::Read { path, source } => {
+                write!(f, "cannot read {}: {source}", path.display())
+            }
+            ProfileError::Parse { path, source } => {
+                write!(f, "{} is malformed: {source}", path.display())
+            }
+            ProfileError::Unknown { name, path, known } => write!(
+                f,
+                "environment {name} is not in {}, known are: {}",
+                path.display(),
+                known.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {}
+
+/// Path of the environments file: SQUID_ENV_FILE or environments.toml.
+pub fn env_file() -> PathBuf {
+    std::env::var_os(ENV_FILE_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_ENV_FILE))
+}
+
+/// Name of the environment given with --env flag or SQUID_ENV.
+pub fn selected(flag: Option<&str>) -> Option<String> {
+    flag.map(str::to_string)
+        .or_else(|| std::env::var(ENV_NAME_ENV).ok())
+}
+
+/// Reads the environment of given name from the environments file.
+pub fn load(name: &str) -> Result<Profile, ProfileError> {
+    let path = env_file();
+    let text = std::fs::read_to_string(&path).map_err(|source| ProfileError::Read {
+        path: path.clone(),
+        source,
+    })?;
+    let mut profiles: HashMap<String, Profile> =
+        toml::from_str(&text).map_err(|source| ProfileError::Parse {
+            path: path.clone(),
+            source,
+        })?;
+    let Some(mut profile) = profiles.remove(name) else {
+        let mut known: Vec<String> = profiles.into_keys().collect();
+        known.sort();
+        return Err(ProfileError::Unknown {
+            name: name.to_string(),
+            path,
+            known,
+        });
+    };
+    profile.name = name.to_string();
+    Ok(profile)
+}
+
+impl Profile {
+    /// Whether requests of the environment can reach a live account. SQUID_API_URL may point anywhere,
+    /// so only the fake exchange stays not live with it.
+    pub fn is_live(&self) -> bool {
+        self.is_live_at(std::env::var(crate::client::API_URL_ENV).ok().as_deref())
+    }
+
+    // Same as `is_live` with URL given by SQUID_API_URL.
+    fn is_live_at(&self, api_url_override: Option<&str>) -> bool {
+        !self.fake_exchange && (self.live || api_url_override.is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(toml: &str) -> Profile {
+        toml::from_str(toml).expect("Profile is valid")
+    }
+
+    #[test]
+    fn url_other_than_production_is_live() {
+        // E.g. a local proxy, which forwards requests to production.
+        let recording = profile(r#"rest_url = "http://127.0.0.1:8080""#);
+        assert!(recording.is_live_at(None));
+    }
+
+    #[test]
+    fn api_not_marked_live_is_live_with_url_override() {
+        let sandbox = profile("rest_url = \"https://sandbox.example\"\nlive = false");
+        assert!(!sandbox.is_live_at(None));
+        assert!(sandbox.is_live_at(Some("http://127.0.0.1:8080")));
+    }
+
+    #[test]
+    fn fake_exchange_is_not_live() {
+        let mock = profile("fake_exchange = true");
+        assert!(!mock.is_live_at(None));
+        assert!(!mock.is_live_at(Some("http://127.0.0.1:8080")));
+    }
+}
@@ -0,0 +1,504 @@
+//! Guard against unintended live trading. Every state changing call is checked before it is signed.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    str::FromStr,
+    sync::{Mutex, OnceLock},
+};
+
+use indexmap::IndexMap;
+use rust_decimal::Decimal;
+use tracing::warn;
+
+use crate::{
+    calls,
+    client::{
+        private_request_over, public_request_over, AsyncTransport, NonceGen, SquidCreds, Transport,
+        VALIDATED_ENDPOINTS,
+    },
+    models::{BaseResponse, OrderInfo, TickerInfo},
+};
+
+/// Opt-in for state changing calls to a live API.
+pub const LIVE_TRADING_ENV: &str = "SQUID_LIVE_TRADING";
+/// Comma separated pairs, which orders can be placed for.
+pub const ALLOWED_PAIRS_ENV: &str = "SQUID_ALLOWED_PAIRS";
+/// Maximum volume * price of one order, in quote currency.
+pub const MAX_ORDER_NOTIONAL_ENV: &str = "SQUID_MAX_ORDER_NOTIONAL";
+/// Maximum volume * price of all orders placed by the process, in quote currency.
+pub const MAX_RUN_NOTIONAL_ENV: &str = "SQUID_MAX_RUN_NOTIONAL";
+/// Minimum distance of limit price from the market price, as a fraction of it, e.g. 0.2 is 20%.
+pub const MIN_MARKET_DISTANCE_ENV: &str = "SQUID_MIN_MARKET_DISTANCE";
+
+const DEFAULT_ALLOWED_PAIRS: &str = "ETHXBT";
+const DEFAULT_MAX_ORDER_NOTIONAL: &str = "0.05";
+const DEFAULT_MAX_RUN_NOTIONAL: &str = "1";
+const DEFAULT_MIN_MARKET_DISTANCE: &str = "0.2";
+
+const STATE_CHANGING_ENDPOINTS: [&str; 8] = [
+    "AddOrder",
+    "AddOrderBatch",
+    "EditOrder",
+    "AmendOrder",
+    "CancelOrder",
+    "CancelOrderBatch",
+    "CancelAll",
+    "CancelAllOrdersAfter",
+];
+
+// Whether requests can reach a live account, see `set_live`.
+static LIVE: OnceLock<bool> = OnceLock::new();
+
+// Notional of orders allowed since the start of the run.
+static RUN_NOTIONAL: Mutex<Decimal> = Mutex::new(Decimal::ZERO);
+
+/// Reason, why the call was refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SafetyError {
+    LiveTradingDisabled {
+        endpoint: String,
+    },
+    PairNotAllowed {
+        pair: String,
+    },
+    /// Only limit orders with absolute price can be kept away from the market.
+    OrderTypeNotAllowed {
+        ordertype: String,
+    },
+    OrderNotional {
+        notional: Decimal,
+        max: Decimal,
+    },
+    RunNotional {
+        notional: Decimal,
+        max: Decimal,
+    },
+    TooCloseToMarket {
+        price: Decimal,
+        market: Decimal,
+        min_distance: Decimal,
+    },
+    /// Order could not be checked, e.g. market price is not known.
+    Unchecked(String),
+}
+
+impl SafetyError {
+    /// Short name of the violated rule.
+    pub fn rule(&self) -> &'static str {
+        match self {
+            SafetyError::LiveTradingDisabled { .. } => "live_trading",
+            SafetyError::PairNotAllowed { .. } => "pair",
+            SafetyError::OrderTypeNotAllowed { .. } => "order_type",
+            SafetyError::OrderNotional { .. } => "order_notional",
+            SafetyError::RunNotional { .. } => "run_notional",
+            SafetyError::TooCloseToMarket { .. } => "market_distance",
+            SafetyError::Unchecked(_) => "unchecked",
+        }
+    }
+}
+
+impl fmt::Display for SafetyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SafetyError::LiveTradingDisabled { endpoint } => write!(
+                f,
+                "{endpoint} changes state of the live account, set {LIVE_TRADING_ENV} to allow it"
+            ),
+            SafetyError::PairNotAllowed { pair } => {
+                write!(f, "pair {pair} is not in {ALLOWED_PAIRS_ENV}")
+            }
+            SafetyError::OrderTypeNotAllowed { ordertype } => write!(
+                f,
+                "{ordertype} order may fill, only limit orders with absolute price are allowed"
+            ),
+            SafetyError::OrderNotional { notional, max } => {
+                write!(f, "order notional {notional} is over {max}")
+            }
+            SafetyError::RunNotional { notional, max } => {
+                write!(f, "notional {notional} of orders in this run is over {max}")
+            }
+            SafetyError::TooCloseToMarket {
+                price,
+                market,
+                min_distance,
+            } => write!(
+                f,
+                "price {price} is closer than {min_distance} to the market price {market}"
+            ),
+            SafetyError::Unchecked(reason) => write!(f, "order cannot be checked: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for SafetyError {}
+
+struct Limits {
+    live_trading: bool,
+    allowed_pairs: Vec<String>,
+    max_order_notional: Decimal,
+    max_run_notional: Decimal,
+    min_market_distance: Decimal,
+}
+
+fn env_decimal(name: &str, default: &str) -> Decimal {
+    let val = std::env::var(name).unwrap_or_else(|_| default.to_string());
+    Decimal::from_str(&val).unwrap_or_else(|_| panic!("{name} should be a number, got {val}"))
+}
+
+impl Limits {
+    fn from_env() -> Limits {
+        let pairs =
+            std::env::var(ALLOWED_PAIRS_ENV).unwrap_or_else(|_| DEFAULT_ALLOWED_PAIRS.to_string());
+        Limits {
+            live_trading: std::env::var_os(LIVE_TRADING_ENV).is_some(),
+            allowed_pairs: pairs
+                .split(',')
+                .map(|pair| pair.trim().to_uppercase())
+                .collect(),
+            max_order_notional: env_decimal(MAX_ORDER_NOTIONAL_ENV, DEFAULT_MAX_ORDER_NOTIONAL),
+            max_run_notional: env_decimal(MAX_RUN_NOTIONAL_ENV, DEFAULT_MAX_RUN_NOTIONAL),
+            min_market_distance: env_decimal(MIN_MARKET_DISTANCE_ENV, DEFAULT_MIN_MARKET_DISTANCE),
+        }
+    }
+}
+
+/// Order, which would be open on the account after the call.
+#[derive(Debug)]
+struct PlannedOrder {
+    pair: String,
+    side: String,
+    ordertype: String,
+    volume: Decimal,
+    price: Option<Decimal>,
+    volume_in_quote: bool,
+}
+
+fn number(val: &str) -> Option<Decimal> {
+    Decimal::from_str(val).ok()
+}
+
+/// Relative prices, e.g. "+10", "#5" or "2%", depend on the market and are not known.
+fn absolute_price(val: &str) -> Option<Decimal> {
+    let relative = val.starts_with(['+', '-', '#']) || val.ends_with('%');
+    number(val).filter(|_| !relative)
+}
+
+impl PlannedOrder {
+    fn from_params(params: &HashMap<&str, &str>) -> Result<PlannedOrder, SafetyError> {
+        let param = |name: &str| {
+            params
+                .get(name)
+                .map(|val| val.to_string())
+                .ok_or_else(|| SafetyError::Unchecked(format!("{name} is missing")))
+        };
+        let volume = param("volume")?;
+        Ok(PlannedOrder {
+            pair: param("pair")?,
+            side: param("type")?,
+            ordertype: param("ordertype")?,
+            volume: number(&volume).ok_or_else(|| {
+                SafetyError::Unchecked(format!("volume {volume} is not a number"))
+            })?,
+            price: params.get("price").and_then(|price| absolute_price(price)),
+            volume_in_quote: params
+                .get("oflags")
+                .is_some_and(|oflags| oflags.split(',').any(|flag| flag == "viqc")),
+        })
+    }
+
+    fn notional(&self) -> Decimal {
+        match (self.volume_in_quote, self.price) {
+            (true, _) => self.volume,
+            (false, Some(price)) => self.volume * price,
+            (false, None) => Decimal::MAX,
+        }
+    }
+}
+
+/// Orders of AddOrderBatch, e.g. "orders[0][price]", with pair common to all of them.
+fn batch_orders<'a>(params: &[(&'a str, &'a str)]) -> Vec<HashMap<&'a str, &'a str>> {
+    let pair = params.iter().find(|(key, _)| *key == "pair");
+    let mut orders: Vec<HashMap<&str, &str>> = Vec::new();
+    for (key, val) in params {
+        let Some((index, name)) = key
+            .strip_prefix("orders[")
+            .and_then(|rest| rest.strip_suffix(']'))
+            .and_then(|rest| rest.split_once("]["))
+        else {
+            continue;
+        };
+        let Ok(index) = index.parse::<usize>() else {
+            continue;
+        };
+        if index >= orders.len() {
+            orders.resize_with(index + 1, HashMap::new);
+        }
+        orders[index].insert(name, val);
+    }
+    for order in &mut orders {
+        if let Some((key, val)) = pair {
+            order.insert(key, val);
+        }
+    }
+    orders
+}
+
+/// Open order, which is edited or amended.
+async fn query_order(
+    transport: &impl Transport,
+    creds: &SquidCreds,
+    txid: &str,
+) -> Result<PlannedOrder, SafetyError> {
+    let unchecked = SafetyError::Unchecked;
+    let data = IndexMap::from([
+        ("nonce", NonceGen::default().get_nonce()),
+        ("txid", txid.to_string()),
+    ]);
+    let response = private_request_over(transport, "/0/private/QueryOrders", data, creds)
+        .await
+        .map_err(|e| unchecked(format!("QueryOrders failed: {e}")))?;
+    let mut body = serde_json::from_str::<BaseResponse<HashMap<String, OrderInfo>>>(&response.text)
+        .map_err(|_| unchecked(format!("order {txid} is not known")))?;
+    let order = body
+        .result
+        .remove(txid)
+        .ok_or_else(|| unchecked(format!("order {txid} is not returned")))?;
+    let descr = |name: &str| order.details.descr.get(name).cloned().unwrap_or_default();
+    Ok(PlannedOrder {
+        pair: descr("pair"),
+        side: descr("type"),
+        ordertype: descr("ordertype"),
+        volume: order.details.vol,
+        price: absolute_price(&descr("price")),
+        volume_in_quote: order.details.oflags.split(',').any(|flag| flag == "viqc"),
+    })
+}
+
+/// Orders, which would be open after the call to `endpoint`.
+async fn planned_orders(
+    transport: &impl Transport,
+    endpoint: &str,
+    params: &[(&str, &str)],
+    creds: &SquidCreds,
+) -> Result<Vec<PlannedOrder>, SafetyError> {
+    let map: HashMap<&str, &str> = params.iter().copied().collect();
+    match endpoint {
+        "AddOrder" => Ok(vec![PlannedOrder::from_params(&map)?]),
+        "AddOrderBatch" => batch_orders(params)
+            .iter()
+            .map(PlannedOrder::from_params)
+            .collect(),
+        "EditOrder" | "AmendOrder" => {
+            let txid = map
+                .get("txid")
+                .ok_or_else(|| SafetyError::Unchecked("txid is missing".to_string()))?;
+            let mut order = query_order(transport, creds, txid).await?;
+            let (volume, price) = match endpoint {
+                "EditOrder" => ("volume", "price"),
+                _ => ("order_qty", "limit_price"),
+            };
+            if let Some(volume) = map.get(volume) {
+                order.volume = number(volume).ok_or_else(|| {
+                    SafetyError::Unchecked(format!("volume {volume} is not a number"))
+                })?;
+            }
+            if let Some(price) = map.get(price) {
+                order.price = absolute_price(price);
+            }
+            Ok(vec![order])
+        }
+        _ => Ok(Vec::new()),
+    }
+}
+
+async fn market_price(
+    transport: &impl Transport,
+    pair: &str,
+    side: &str,
+) -> Result<Decimal, SafetyError> {
+    let unchecked = SafetyError::Unchecked;
+    let response = public_request_over(transport, &calls::ticker(&[("pair", pair)]))
+        .await
+        .map_err(|e| unchecked(format!("Ticker request failed: {e}")))?;
+    let body = serde_json::from_str::<BaseResponse<HashMap<String, TickerInfo>>>(&response.text)
+        .map_err(|_| unchecked(format!("there is no ticker of {pair}")))?;
+    let ticker = body
+        .result
+        .into_values()
+        .next()
+        .ok_or_else(|| unchecked(format!("there is no ticker of {pair}")))?;
+    // Sell would fill at the best bid, buy at the best ask.
+    Ok(match side {
+        "sell" => ticker.b.0,
+        _ => ticker.a.0,
+    })
+}
+
+async fn check_order(
+    transport: &impl Transport,
+    order: &PlannedOrder,
+    limits: &Limits,
+) -> Result<Decimal, SafetyError> {
+    if !limits.allowed_pairs.contains(&order.pair.to_uppercase()) {
+        return Err(SafetyError::PairNotAllowed {
+            pair: order.pair.clone(),
+        });
+    }
+    let price = match (order.ordertype.as_str(), order.price) {
+        ("limit" | "iceberg", Some(price)) => price,
+        _ => {
+            return Err(SafetyError::OrderTypeNotAllowed {
+                ordertype: order.ordertype.clone(),
+            })
+        }
+    };
+    let notional = order.notional();
+    if notional > limits.max_order_notional {
+        return Err(SafetyError::OrderNotional {
+            notional,
+            max: limits.max_order_notional,
+        });
+    }
+    let market = market_price(transport, &order.pair, &order.side).await?;
+    let distance = match order.side.as_str() {
+        "sell" => price - market,
+        _ => market - price,
+    };
+    if distance < market * limits.min_market_distance {
+        return Err(SafetyError::TooCloseToMarket {
+            price,
+            market,
+            min_distance: limits.min_market_distance,
+        });
+    }
+    Ok(notional)
+}
+
+/// Marks requests of the process as not reaching a live account, e.g. of the fake exchange. Every API is live,
+/// whatever its URL is, unless it is marked so. Can be done only once.
+pub fn set_live(live: bool) {
+    LIVE.set(live)
+        .expect("Whether the API is live can be set only once");
+}
+
+/// Whether state changing calls need SQUID_LIVE_TRADING.
+pub fn is_live() -> bool {
+    LIVE.get().copied().unwrap_or(true)
+}
+
+/// Whether the call changes the account. Only orders can be validated, other endpoints ignore validate param.
+fn changes_state(endpoint: &str, params: &[(&str, &str)]) -> bool {
+    let validated =
+        VALIDATED_ENDPOINTS.contains(&endpoint) && params.contains(&("validate", "true"));
+    STATE_CHANGING_ENDPOINTS.contains(&endpoint) && !validated
+}
+
+/// Checks call to private `endpoint` before it is signed. Validated orders do not change anything, so they pass.
+pub async fn check(
+    endpoint: &str,
+    params: &[(&str, &str)],
+    creds: &SquidCreds,
+) -> Result<(), SafetyError> {
+    check_over(&AsyncTransport, endpoint, params, creds).await
+}
+
+/// Same as `check`, market and orders are queried by the transport of the checked call.
+pub(crate) async fn check_over(
+    transport: &impl Transport,
+    endpoint: &str,
+    params: &[(&str, &str)],
+    creds: &SquidCreds,
+) -> Result<(), SafetyError> {
+    check_with(transport, endpoint, params, creds, &Limits::from_env()).await
+}
+
+async fn check_with(
+    transport: &impl Transport,
+    endpoint: &str,
+    params: &[(&str, &str)],
+    creds: &SquidCreds,
+    limits: &Limits,
+) -> Result<(), SafetyError> {
+    if !changes_state(endpoint, params) {
+        return Ok(());
+    }
+    if is_live() && !limits.live_trading {
+        return Err(SafetyError::LiveTradingDisabled {
+            endpoint: endpoint.to_string(),
+        });
+    }
+    let mut notional = Decimal::ZERO;
+    for order in planned_orders(transport, endpoint, params, creds).await? {
+        notional += check_order(transport, &order, limits).await?;
+    }
+    let mut run_notional = RUN_NOTIONAL.lock().expect("Run notional lock is poisoned");
+    if *run_notional + notional > limits.max_run_notional {
+        return Err(SafetyError::RunNotional {
+            notional: *run_notional + notional,
+            max: limits.max_run_notional,
+        });
+    }
+    *run_notional += notional;
+    Ok(())
+}
+
+/// Starts counting notional of a new run, e.g. in monitoring loop.
+pub fn reset_run_notional() {
+    *RUN_NOTIONAL.lock().expect("Run notional lock is poisoned") = Decimal::ZERO;
+}
+
+/// Logs refused call, so it is visible even when the error is only expected by the caller.
+pub(crate) fn log_refused(endpoint: &str, e: &SafetyError) {
+    warn!(
+        endpoint,
+        rule = e.rule(),
+        "call refused before signing: {e}"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> Limits {
+        Limits {
+            live_trading: false,
+            allowed_pairs: vec![DEFAULT_ALLOWED_PAIRS.to_string()],
+            max_order_notional: DEFAULT_MAX_ORDER_NOTIONAL.parse().unwrap(),
+            max_run_notional: DEFAULT_MAX_RUN_NOTIONAL.parse().unwrap(),
+            min_market_distance: DEFAULT_MIN_MARKET_DISTANCE.parse().unwrap(),
+        }
+    }
+
+    async fn rule(endpoint: &str, params: &[(&str, &str)]) -> Result<(), &'static str> {
+        check_with(
+            &AsyncTransport,
+            endpoint,
+            params,
+            &SquidCreds::default(),
+            &limits(),
+        )
+        .await
+        .map_err(|e| e.rule())
+    }
+
+    #[tokio::test]
+    async fn cancel_all_cannot_be_validated() {
+        assert_eq!(
+            rule("CancelAll", &[("validate", "true")]).await,
+            Err("live_trading")
+        );
+        assert_eq!(
+            rule("CancelOrder", &[("txid", "OXXXXX"), ("validate", "true")]).await,
+            Err("live_trading")
+        );
+    }
+
+    #[tokio::test]
+    async fn validated_orders_pass() {
+        let order = [("pair", "ETHXBT"), ("validate", "true")];
+        assert_eq!(rule("AddOrder", &order).await, Ok(()));
+        assert_eq!(rule("EditOrder", &order).await, Ok(()));
+    }
+}
@@ -0,0 +1,23 @@
+use tracing_subscriber::EnvFilter;
+
+const LOG_FORMAT_ENV: &str = "SQUID_LOG_FORMAT";
+const DEFAULT_LOG_FILTER: &str = "warn";
+
+/// Installs global tracing subscriber.
+/// Filter is taken from RUST_LOG (e.g. "growing_squid=debug"), format from SQUID_LOG_FORMAT ("json" or default plain text).
+/// Logs go to stderr so they do not mix with cucumber report printed on stdout.
+pub fn init() {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER));
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+    match std::env::var(LOG_FORMAT_ENV).as_deref() {
+        Ok("json") => builder
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .init(),
+        _ => builder.init(),
+    }
+}
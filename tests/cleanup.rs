@@ -0,0 +1,116 @@
+//! Cancels orders left open by a scenario and disarms its dead man's switch, whatever its outcome was.
+
+use std::sync::Mutex;
+
+use growing_squid::{
+    client::{cancel_all_orders_after, cancel_order_batch, get_open_orders, MAX_BATCH_CANCELS},
+    models::{BaseResponse, CriticalErrorResponse, OpenOrders},
+};
+use tracing::{error, info};
+
+use crate::World;
+
+/// Orders of a scenario, which could not be cancelled.
+#[derive(Debug)]
+struct Leak {
+    scenario: String,
+    txids: Vec<String>,
+    reason: String,
+}
+
+// Scenarios report leaks from their own after hooks, so they are gathered globally.
+static LEAKS: Mutex<Vec<Leak>> = Mutex::new(Vec::new());
+
+/// Txids of orders placed by the scenario, which are still open.
+async fn still_open(w: &mut World) -> Result<Vec<String>, String> {
+    let response = get_open_orders(&w.creds, &mut w.nonce_gen)
+        .await
+        .map_err(|e| format!("OpenOrders request failed: {e}"))?;
+    let body = serde_json::from_str::<BaseResponse<OpenOrders>>(&response.text)
+        .map_err(|e| format!("OpenOrders response is malformed: {e}"))?;
+    if !body.error.is_empty() {
+        return Err(format!("OpenOrders failed with {:?}", body.error));
+    }
+    Ok(w.placed_orders
+        .iter()
+        .filter(|txid| body.result.open.contains_key(*txid))
+        .cloned()
+        .collect())
+}
+
+async fn cancel(w: &mut World) -> Result<(), String> {
+    let open = still_open(w).await?;
+    if open.is_empty() {
+        return Ok(());
+    }
+    info!(orders = ?open, "cancelling orders left by the scenario");
+    for txids in open.chunks(MAX_BATCH_CANCELS) {
+        let txids: Vec<&str> = txids.iter().map(String::as_str).collect();
+        cancel_order_batch(&w.creds, &mut w.nonce_gen, &txids)
+            .await
+            .map_err(|e| format!("CancelOrderBatch request failed: {e}"))?;
+    }
+    // Cancellation is verified, the response alone does not say which orders were cancelled.
+    match still_open(w).await?.as_slice() {
+        [] => Ok(()),
+        open => Err(format!("orders {open:?} are still open after cancellation")),
+    }
+}
+
+/// After hook of every scenario. Cancels its open orders and records them as leaked, if that fails.
+pub async fn cancel_scenario_orders(w: &mut World) {
+    if w.placed_orders.is_empty() {
+        return;
+    }
+    if let Err(reason) = cancel(w).await {
+        error!(scenario = %w.scenario, orders = ?w.placed_orders, %reason, "orders leaked");
+        LEAKS.lock().expect("Leaks lock is poisoned").push(Leak {
+            scenario: w.scenario.clone(),
+            txids: std::mem::take(&mut w.placed_orders),
+            reason,
+        });
+    }
+}
+
+/// Whether the response has no errors.
+pub fn is_success(response_text: &str) -> bool {
+    serde_json::from_str::<CriticalErrorResponse>(response_text)
+        .is_ok_and(|body| body.error.is_empty())
+}
+
+/// After hook of every scenario. Disarms the dead man's switch, when the scenario left it armed.
+pub async fn disarm_dead_mans_switch(w: &mut World) -> Result<(), String> {
+    if !w.dead_mans_switch_armed {
+        return Ok(());
+    }
+    info!("disarming dead man's switch left by the scenario");
+    let response = cancel_all_orders_after(&w.creds, &mut w.nonce_gen, 0)
+        .await
+        .map_err(|e| format!("CancelAllOrdersAfter request failed: {e}"))?;
+    if !is_success(&response.text) {
+        return Err(format!(
+            "CancelAllOrdersAfter failed with {}",
+            response.text
+        ));
+    }
+    w.dead_mans_switch_armed = false;
+    Ok(())
+}
+
+/// Prints orders, which may be left open on the account. Returns their number.
+pub fn report() -> usize {
+    let leaks = std::mem::take(&mut *LEAKS.lock().expect("Leaks lock is poisoned"));
+    if leaks.is_empty() {
+        return 0;
+    }
+    println!("\nLeaked orders, check them on the account and cancel manually:");
+    for leak in &leaks {
+        println!(
+            "{}: {} ({})",
+            leak.scenario,
+            leak.txids.join(", "),
+            leak.reason
+        );
+    }
+    leaks.iter().map(|leak| leak.txids.len()).sum()
+}
@@ -0,0 +1,141 @@
+//! Decides, which scenarios can run with available credentials and selected environment.
+//!
+//! Recognised tags of features and scenarios:
+//! * @public - public endpoints only, runs everywhere
+//! * @private - needs API key, but does not change the account
+//! * @trading - places or cancels orders, on a live API it needs SQUID_LIVE_TRADING
+//! * @live-only / @mock-only - depends on the live API or the fake exchange
+//! * @real-orders - works with placed orders, so it cannot run with SQUID_VALIDATE_ONLY
+//! * @cancels-all-orders - cancels every order of the account, on a live API it needs SQUID_ALLOW_CANCEL_ALL
+//! * @permission-<name> - needs the permission of API key, e.g. @permission-query-funds
+
+use std::{
+    collections::BTreeMap,
+    sync::{Mutex, OnceLock},
+};
+
+use cucumber::gherkin::{Feature, Scenario};
+use growing_squid::{
+    client::{validate_only, NonceGen},
+    permissions::{self, Access, Permission},
+    safety::{self, LIVE_TRADING_ENV},
+};
+
+use crate::{current_profile, fake_exchange};
+
+/// Opt-in of scenarios, which cancel all orders of the live account, including the ones not placed by the suite.
+pub const ALLOW_CANCEL_ALL_ENV: &str = "SQUID_ALLOW_CANCEL_ALL";
+
+// Permissions, which the API key certainly does not have.
+static DENIED: OnceLock<Vec<Permission>> = OnceLock::new();
+
+// Scenarios are filtered while features are parsed, reasons are kept until the end of the run.
+static SKIPPED: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+/// Why the scenario cannot run, None when it can.
+fn skip_reason(tags: &[&str]) -> Option<String> {
+    let mock = fake_exchange::is_running();
+    let live = safety::is_live();
+    let credentials = &current_profile().credentials;
+    let has_creds = mock || credentials.load().is_some();
+    let needs_creds = tags.contains(&"private") || tags.contains(&"trading");
+    if needs_creds && !has_creds {
+        return Some(format!(
+            "{} and {} are not set",
+            credentials.key_env, credentials.secret_env
+        ));
+    }
+    let denied = DENIED.get().map(Vec::as_slice).unwrap_or_default();
+    if let Some(permission) = denied
+        .iter()
+        .find(|permission| tags.contains(&format!("permission-{permission}").as_str()))
+    {
+        return Some(format!("API key has no {permission} permission"));
+    }
+    let reason = if tags.contains(&"trading")
+        && live
+        && std::env::var_os(LIVE_TRADING_ENV).is_none()
+    {
+        Some("trading on live account is not enabled with SQUID_LIVE_TRADING")
+    } else if tags.contains(&"cancels-all-orders")
+        && live
+        && std::env::var_os(ALLOW_CANCEL_ALL_ENV).is_none()
+    {
+        Some("cancels all orders of the account, which is not allowed without SQUID_ALLOW_CANCEL_ALL")
+    } else if tags.contains(&"live-only") && mock {
+        Some("needs the live API")
+    } else if tags.contains(&"mock-only") && !mock {
+        Some("needs the fake exchange, run it with SQUID_MOCK or --env mock")
+    } else if tags.contains(&"real-orders") && validate_only() {
+        Some("orders are only validated with SQUID_VALIDATE_ONLY")
+    } else {
+        None
+    };
+    reason.map(str::to_string)
+}
+
+/// Preflight of the run. Probes permissions of the API key, scenarios needing the denied ones are skipped.
+pub async fn check_permissions() {
+    let creds = if fake_exchange::is_running() {
+        fake_exchange::credentials()
+    } else {
+        match current_profile().credentials.load() {
+            Some(creds) => creds,
+            None => return,
+        }
+    };
+    let mut denied = Vec::new();
+    for (permission, access) in permissions::probe(&creds, &mut NonceGen::default()).await {
+        match access {
+            Access::Granted => {}
+            Access::Denied => denied.push(permission),
+            // Scenarios are run then, so they fail with the real reason.
+            Access::Unknown(reason) => {
+                tracing::warn!(%permission, %reason, "permission of API key is unknown")
+            }
+        }
+    }
+    if !denied.is_empty() {
+        tracing::warn!(?denied, "API key has not all permissions");
+    }
+    DENIED
+        .set(denied)
+        .expect("Permissions are checked only once");
+}
+
+/// Filter of `filter_run`, which records skipped scenarios with their reason.
+pub fn is_runnable(feature: &Feature, scenario: &Scenario) -> bool {
+    let tags: Vec<&str> = feature
+        .tags
+        .iter()
+        .chain(&scenario.tags)
+        .map(String::as_str)
+        .collect();
+    let Some(reason) = skip_reason(&tags) else {
+        return true;
+    };
+    SKIPPED
+        .lock()
+        .expect("Skipped scenarios lock is poisoned")
+        .insert(
+            format!(
+                "{} / {} (line {})",
+                feature.name, scenario.name, scenario.position.line
+            ),
+            reason,
+        );
+    false
+}
+
+/// Prints skipped scenarios with the reason. Returns their number.
+pub fn report() -> usize {
+    let skipped = std::mem::take(&mut *SKIPPED.lock().expect("Skipped scenarios lock is poisoned"));
+    if skipped.is_empty() {
+        return 0;
+    }
+    println!("\nSkipped scenarios:");
+    for (scenario, reason) in &skipped {
+        println!("{scenario}: {reason}");
+    }
+    skipped.len()
+}
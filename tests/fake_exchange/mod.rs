@@ -0,0 +1,267 @@
+//! Local fake of the exchange, so features can run offline. Enabled with SQUID_MOCK env variable.
+
+use growing_squid::{
+    client::{create_signature, SquidCreds},
+    clock::{Clock, FakeClock},
+};
+use hyper::{
+    header::HeaderMap,
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server, StatusCode,
+};
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+};
+
+mod orders;
+mod private;
+mod public;
+
+const FAKE_API_KEY: &str = "squid-fake-api-key";
+// base64 of "squid-fake-api-secret"
+const FAKE_API_SECRET: &str = "c3F1aWQtZmFrZS1hcGktc2VjcmV0";
+
+const INVALID_ARGUMENTS: &str = "EGeneral:Invalid arguments";
+const UNKNOWN_METHOD: &str = "EGeneral:Unknown method";
+const UNKNOWN_ASSET: &str = "EQuery:Unknown asset";
+const UNKNOWN_PAIR: &str = "EQuery:Unknown asset pair";
+const INVALID_KEY: &str = "EAPI:Invalid key";
+const INVALID_SIGNATURE: &str = "EAPI:Invalid signature";
+const INVALID_NONCE: &str = "EAPI:Invalid nonce";
+
+type Query = HashMap<String, String>;
+type Outcome = Result<Value, &'static str>;
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+// Time of the exchange, which can be skewed from the system clock of the suite.
+static CLOCK: OnceLock<FakeClock> = OnceLock::new();
+
+/// State of the fake account, shared by all requests.
+#[derive(Debug, Default)]
+struct Exchange {
+    last_nonce: u64,
+    orders: orders::Book,
+}
+
+pub struct FakeExchange {
+    addr: SocketAddr,
+}
+
+impl FakeExchange {
+    /// Starts the exchange in the background on a free local port, with its time read from the clock.
+    pub fn start(clock: FakeClock) -> FakeExchange {
+        CLOCK
+            .set(clock)
+            .expect("Fake exchange can be started only once");
+        let exchange = Arc::new(Mutex::new(Exchange::default()));
+        let make_service = make_service_fn(move |_conn| {
+            let exchange = exchange.clone();
+            async move { Ok::<_, Infallible>(service_fn(move |req| handle(req, exchange.clone()))) }
+        });
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let addr = server.local_addr();
+        tokio::spawn(async move {
+            if let Err(e) = server.await {
+                tracing::error!("fake exchange failed: {e}");
+            }
+        });
+        RUNNING.store(true, Ordering::SeqCst);
+        tracing::info!(%addr, "fake exchange is listening");
+        FakeExchange { addr }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+}
+
+pub fn is_running() -> bool {
+    RUNNING.load(Ordering::SeqCst)
+}
+
+/// Credentials accepted by the fake exchange.
+pub fn credentials() -> SquidCreds {
+    SquidCreds {
+        key: FAKE_API_KEY.to_string(),
+        secret: FAKE_API_SECRET.to_string(),
+        ..SquidCreds::default()
+    }
+}
+
+/// Body of a successful response.
+fn result(result: Value) -> Value {
+    json!({ "error": [], "result": result })
+}
+
+/// Body of a response with error, e.g. "EGeneral:Invalid arguments".
+fn error(error: &str) -> Value {
+    json!({ "error": [error] })
+}
+
+/// Decodes percent-encoded form key or value, e.g. "orders%5B0%5D" -> "orders[0]".
+fn form_unescape(text: &str) -> String {
+    let text = text.replace('+', " ");
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+fn parse_query(query: &str) -> Query {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((key, val)) => (form_unescape(key), form_unescape(val)),
+            None => (form_unescape(pair), String::new()),
+        })
+        .collect()
+}
+
+/// Entries of nested list params, e.g. "orders[0][price]" -> [{"price": ...}], ordered by index.
+fn nested_params(params: &Query, name: &str) -> Result<Vec<Query>, &'static str> {
+    let mut entries: Vec<(usize, Query)> = Vec::new();
+    for (key, val) in params {
+        let Some(nested) = key.strip_prefix(name).and_then(|key| key.strip_prefix('[')) else {
+            continue;
+        };
+        let (index, field) = nested
+            .split_once("][")
+            .and_then(|(index, field)| Some((index.parse().ok()?, field.strip_suffix(']')?)))
+            .ok_or(INVALID_ARGUMENTS)?;
+        match entries.iter_mut().find(|(i, _)| *i == index) {
+            Some((_, entry)) => {
+                entry.insert(field.to_string(), val.clone());
+            }
+            None => entries.push((index, Query::from([(field.to_string(), val.clone())]))),
+        }
+    }
+    entries.sort_by_key(|(index, _)| *index);
+    Ok(entries.into_iter().map(|(_, entry)| entry).collect())
+}
+
+/// Values of indexed list params, e.g. "orders[0]", ordered by index.
+fn indexed_params(params: &Query, name: &str) -> Result<Vec<String>, &'static str> {
+    let mut values: Vec<(usize, String)> = params
+        .iter()
+        .filter_map(|(key, val)| {
+            let index = key
+                .strip_prefix(name)?
+                .strip_prefix('[')?
+                .strip_suffix(']')?;
+            Some(index.parse().map(|index| (index, val.clone())))
+        })
+        .collect::<Result<_, _>>()
+        .map_err(|_| INVALID_ARGUMENTS)?;
+    values.sort_by_key(|(index, _)| *index);
+    Ok(values.into_iter().map(|(_, val)| val).collect())
+}
+
+/// Value of numeric query parameter, if it is given.
+fn number_param(query: &Query, name: &str) -> Result<Option<i64>, &'static str> {
+    query
+        .get(name)
+        .map(|val| val.parse().map_err(|_| INVALID_ARGUMENTS))
+        .transpose()
+}
+
+/// Current time of the exchange.
+fn now_utc() -> chrono::DateTime<chrono::Utc> {
+    let ms = CLOCK.get().expect("Fake exchange is started").now_ms();
+    chrono::TimeZone::timestamp_millis(&chrono::Utc, ms)
+}
+
+fn now() -> f64 {
+    now_utc().timestamp_millis() as f64 / 1000.0
+}
+
+/// Checks API key, signature and nonce of private request, the same way as the exchange does.
+fn authenticate(
+    headers: &HeaderMap,
+    uri_path: &str,
+    body: &str,
+    params: &Query,
+    exchange: &mut Exchange,
+) -> Result<(), &'static str> {
+    let header = |name| headers.get(name).and_then(|val| val.to_str().ok());
+    if header("API-Key") != Some(FAKE_API_KEY) {
+        return Err(INVALID_KEY);
+    }
+    let nonce = params.get("nonce").ok_or(INVALID_NONCE)?;
+    let expected_signature = create_signature(FAKE_API_SECRET, uri_path, body, nonce)
+        .expect("Fake API secret is valid base64");
+    if header("API-Sign") != Some(expected_signature.as_str()) {
+        return Err(INVALID_SIGNATURE);
+    }
+    let nonce: u64 = nonce.parse().map_err(|_| INVALID_NONCE)?;
+    if nonce <= exchange.last_nonce {
+        return Err(INVALID_NONCE);
+    }
+    exchange.last_nonce = nonce;
+    Ok(())
+}
+
+async fn handle(
+    req: Request<Body>,
+    exchange: Arc<Mutex<Exchange>>,
+) -> Result<Response<Body>, Infallible> {
+    let path = req.uri().path().to_string();
+    let query = parse_query(req.uri().query().unwrap_or_default());
+    let headers = req.headers().clone();
+    let body = hyper::body::to_bytes(req.into_body())
+        .await
+        .map(|bytes| String::from_utf8_lossy(&bytes).to_string())
+        .unwrap_or_default();
+
+    let body = if let Some(endpoint) = path.strip_prefix("/0/public/") {
+        Some(public::respond(endpoint, &query))
+    } else if let Some(endpoint) = path.strip_prefix("/0/private/") {
+        let params = parse_query(&body);
+        let mut exchange = exchange.lock().expect("Fake exchange lock is poisoned");
+        Some(
+            match authenticate(&headers, &path, &body, &params, &mut exchange) {
+                Ok(()) => {
+                    exchange.orders.check_cancel_all_after();
+                    private::respond(endpoint, &params, &mut exchange)
+                }
+                Err(e) => error(e),
+            },
+        )
+    } else if path == "/" {
+        Some(Value::String("Fake exchange".to_string()))
+    } else {
+        None
+    };
+    let response = match body {
+        Some(body) => Response::builder()
+            .header("Content-Type", "application/json")
+            .body(Body::from(body.to_string())),
+        None => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty()),
+    };
+    Ok(response.expect("Cannot build fake exchange response"))
+}
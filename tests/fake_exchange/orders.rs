@@ -0,0 +1,550 @@
+use growing_squid::client::{MAX_BATCH_CANCELS, MAX_BATCH_ORDERS};
+use indexmap::IndexMap;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use super::{
+    indexed_params, nested_params, now, now_utc, public, Outcome, Query, INVALID_ARGUMENTS,
+};
+
+const ORDER_MINIMUM_NOT_MET: &str = "EOrder:Order minimum not met";
+const COST_MINIMUM_NOT_MET: &str = "EOrder:Cost minimum not met";
+const INVALID_PRICE: &str = "EOrder:Invalid price";
+const TICK_SIZE_CHECK_FAILED: &str = "EOrder:Tick size check failed";
+const INVALID_VOLUME: &str = "EGeneral:Invalid arguments:volume";
+const UNKNOWN_ORDER: &str = "EOrder:Unknown order";
+
+const MIN_BATCH_ORDERS: usize = 2;
+
+const ORDER_TYPES: [&str; 10] = [
+    "market",
+    "limit",
+    "iceberg",
+    "stop-loss",
+    "take-profit",
+    "stop-loss-limit",
+    "take-profit-limit",
+    "trailing-stop",
+    "trailing-stop-limit",
+    "settle-position",
+];
+const ORDER_FLAGS: [&str; 5] = ["post", "fcib", "fciq", "nompp", "viqc"];
+const TIME_IN_FORCE: [&str; 3] = ["GTC", "IOC", "GTD"];
+
+/// Orders placed on the fake account.
+#[derive(Debug, Default)]
+pub(super) struct Book {
+    open: IndexMap<String, Value>,
+    closed: IndexMap<String, Value>,
+    // Params, with which the open orders were placed, so they can be edited.
+    requests: HashMap<String, Query>,
+    last_id: u64,
+    // Time, when all orders are cancelled by CancelAllOrdersAfter.
+    cancel_all_at: Option<f64>,
+}
+
+/// Number with precision used by the exchange for the pair, e.g. ("0.011", 8) -> "0.01100000".
+fn format_decimal(val: &str, decimals: u64) -> Result<String, &'static str> {
+    let val: f64 = val.parse().map_err(|_| INVALID_ARGUMENTS)?;
+    Ok(format!("{val:.prec$}", prec = decimals as usize))
+}
+
+/// Whether the number has more significant decimals than allowed.
+fn too_precise(val: &str, decimals: u64) -> bool {
+    val.split_once('.')
+        .is_some_and(|(_, fraction)| fraction.trim_end_matches('0').len() as u64 > decimals)
+}
+
+/// Price can be relative to the market price with +, - or # prefix, and given in percents, e.g. "+1.5%".
+fn format_price(val: &str, decimals: u64) -> Result<String, &'static str> {
+    let (prefix, price) = match val.strip_prefix(['+', '-', '#']) {
+        Some(price) => (&val[..1], price),
+        None => ("", val),
+    };
+    match price.strip_suffix('%') {
+        Some(percent) => Ok(format!("{prefix}{}%", format_decimal(percent, 2)?)),
+        None if too_precise(price, decimals) => Err(INVALID_PRICE),
+        None => Ok(format!("{prefix}{}", format_decimal(price, decimals)?)),
+    }
+}
+
+/// Checks tick size of absolute price and minimum cost of the order, which has it known.
+fn check_price(params: &Query, pair_info: &Value, ordertype: &str) -> Result<(), &'static str> {
+    let number = |val: &Value| val.as_str().unwrap().parse::<f64>().unwrap();
+    let Some(price) = params
+        .get("price")
+        .filter(|price| !price.starts_with(['+', '-', '#']) && !price.ends_with('%'))
+    else {
+        return Ok(());
+    };
+    let price: f64 = price.parse().map_err(|_| INVALID_ARGUMENTS)?;
+    let ticks = price / number(&pair_info["tick_size"]);
+    if (ticks - ticks.round()).abs() > 1e-6 {
+        return Err(TICK_SIZE_CHECK_FAILED);
+    }
+    let volume: f64 = params["volume"].parse().map_err(|_| INVALID_ARGUMENTS)?;
+    if matches!(ordertype, "limit" | "iceberg") && price * volume < number(&pair_info["costmin"]) {
+        return Err(COST_MINIMUM_NOT_MET);
+    }
+    Ok(())
+}
+
+fn is_validation(params: &Query) -> bool {
+    params.get("validate").map(String::as_str) == Some("true")
+}
+
+fn flag(params: &Query, name: &str) -> Result<bool, &'static str> {
+    match params.get(name).map(String::as_str) {
+        None | Some("false") => Ok(false),
+        Some("true") => Ok(true),
+        Some(_) => Err(INVALID_ARGUMENTS),
+    }
+}
+
+/// Absolute unix time of starttm or expiretm param, which can be also "+<seconds>" from now. 0 means now.
+fn order_time(params: &Query, name: &str) -> Result<i64, &'static str> {
+    let Some(time) = params.get(name) else {
+        return Ok(0);
+    };
+    match time.strip_prefix('+') {
+        Some(secs) => Ok(now() as i64 + secs.parse::<i64>().map_err(|_| INVALID_ARGUMENTS)?),
+        None => time.parse().map_err(|_| INVALID_ARGUMENTS),
+    }
+}
+
+/// Description of order type and its prices, e.g. "stop loss 0.50000 -> limit 0.49000".
+fn describe_type(ordertype: &str, price: &str, price2: &str) -> String {
+    match ordertype {
+        "market" => "market".to_string(),
+        "settle-position" => "settle position".to_string(),
+        "limit" | "iceberg" => format!("{ordertype} {price}"),
+        _ => match ordertype.strip_suffix("-limit") {
+            Some(trigger) => format!("{} {price} -> limit {price2}", trigger.replace('-', " ")),
+            None => format!("{} {price}", ordertype.replace('-', " ")),
+        },
+    }
+}
+
+/// Prices of the order type from `price` and `price2` params, formatted with pair decimals.
+/// Params of conditional close order have `prefix`, e.g. "close[price]".
+fn order_prices(
+    params: &Query,
+    prefix: &str,
+    ordertype: &str,
+    decimals: u64,
+) -> Result<(String, String), &'static str> {
+    let param = |name: &str| match prefix {
+        "" => params.get(name),
+        _ => params.get(&format!("{prefix}[{name}]")),
+    };
+    let needs_price = !matches!(ordertype, "market" | "settle-position");
+    let needs_price2 = ordertype.ends_with("-limit");
+    let price = match param("price") {
+        Some(price) if needs_price => format_price(price, decimals)?,
+        None if !needs_price => "0".to_string(),
+        _ => return Err(INVALID_ARGUMENTS),
+    };
+    let price2 = match param("price2") {
+        Some(price2) if needs_price2 => format_price(price2, decimals)?,
+        None if !needs_price2 => "0".to_string(),
+        _ => return Err(INVALID_ARGUMENTS),
+    };
+    // Trailing stops follow the market, so their prices are always relative offsets.
+    if ordertype.starts_with("trailing-stop")
+        && (!price.starts_with('+') || (needs_price2 && !price2.starts_with(['+', '-'])))
+    {
+        return Err(INVALID_ARGUMENTS);
+    }
+    Ok((price, price2))
+}
+
+/// New order from AddOrder `params`, checked the same way as by the exchange.
+fn build_order(params: &Query) -> Result<Value, &'static str> {
+    let pairs = public::pairs(params, true)?;
+    let [pair] = pairs.as_slice() else {
+        return Err(INVALID_ARGUMENTS);
+    };
+    let pair_info = &public::fixture("asset_pairs")[pair];
+    let side = params.get("type").map(String::as_str);
+    let Some(side @ ("buy" | "sell")) = side else {
+        return Err(INVALID_ARGUMENTS);
+    };
+    let ordertype = params.get("ordertype").ok_or(INVALID_ARGUMENTS)?;
+    if !ORDER_TYPES.contains(&ordertype.as_str()) {
+        return Err(INVALID_ARGUMENTS);
+    }
+    let volume = params.get("volume").ok_or(INVALID_ARGUMENTS)?;
+    let ordermin: f64 = pair_info["ordermin"].as_str().unwrap().parse().unwrap();
+    if volume.parse::<f64>().map_err(|_| INVALID_ARGUMENTS)? < ordermin {
+        return Err(ORDER_MINIMUM_NOT_MET);
+    }
+    let lot_decimals = pair_info["lot_decimals"].as_u64().unwrap();
+    let pair_decimals = pair_info["pair_decimals"].as_u64().unwrap();
+    if too_precise(volume, lot_decimals) {
+        return Err(INVALID_VOLUME);
+    }
+    let vol = format_decimal(volume, lot_decimals)?;
+    let (price, price2) = order_prices(params, "", ordertype, pair_decimals)?;
+    check_price(params, pair_info, ordertype)?;
+
+    // Iceberg shows only part of its volume in the book.
+    match (ordertype.as_str(), params.get("displayvol")) {
+        ("iceberg", Some(displayvol)) => {
+            let displayvol: f64 = displayvol.parse().map_err(|_| INVALID_ARGUMENTS)?;
+            let volume: f64 = vol.parse().unwrap();
+            if displayvol >= volume || displayvol < volume / 15.0 {
+                return Err(INVALID_ARGUMENTS);
+            }
+        }
+        ("iceberg", None) | (_, Some(_)) => return Err(INVALID_ARGUMENTS),
+        _ => {}
+    }
+
+    let oflags: Vec<&str> = params
+        .get("oflags")
+        .map(|oflags| oflags.split(',').filter(|flag| !flag.is_empty()).collect())
+        .unwrap_or_default();
+    if oflags.iter().any(|flag| !ORDER_FLAGS.contains(flag))
+        || (oflags.contains(&"fcib") && oflags.contains(&"fciq"))
+        || (oflags.contains(&"post") && ordertype == "market")
+    {
+        return Err(INVALID_ARGUMENTS);
+    }
+
+    let timeinforce = params.get("timeinforce").map_or("GTC", String::as_str);
+    if !TIME_IN_FORCE.contains(&timeinforce) {
+        return Err(INVALID_ARGUMENTS);
+    }
+    let starttm = order_time(params, "starttm")?;
+    let expiretm = order_time(params, "expiretm")?;
+    if (timeinforce == "GTD" && expiretm == 0) || (expiretm != 0 && expiretm <= starttm) {
+        return Err(INVALID_ARGUMENTS);
+    }
+
+    let leverage = match params.get("leverage").map(String::as_str) {
+        None | Some("none") => None,
+        Some(leverage) => {
+            let allowed = &pair_info[format!("leverage_{side}")];
+            let leverage: u64 = leverage.parse().map_err(|_| INVALID_ARGUMENTS)?;
+            if !allowed.as_array().unwrap().contains(&json!(leverage)) {
+                return Err(INVALID_ARGUMENTS);
+            }
+            Some(leverage)
+        }
+    };
+    if leverage.is_none() && (ordertype == "settle-position" || flag(params, "reduce_only")?) {
+        return Err(INVALID_ARGUMENTS);
+    }
+    if leverage.is_some() && oflags.contains(&"viqc") {
+        return Err(INVALID_ARGUMENTS);
+    }
+
+    let userref: i64 = params
+        .get("userref")
+        .map(|userref| userref.parse().map_err(|_| INVALID_ARGUMENTS))
+        .transpose()?
+        .unwrap_or(0);
+    let cl_ord_id = params.get("cl_ord_id");
+    if cl_ord_id.is_some() && params.contains_key("userref") {
+        return Err(INVALID_ARGUMENTS);
+    }
+
+    let close = match params.get("close[ordertype]") {
+        Some(close_type) => {
+            if !ORDER_TYPES.contains(&close_type.as_str())
+                || matches!(close_type.as_str(), "market" | "settle-position")
+            {
+                return Err(INVALID_ARGUMENTS);
+            }
+            let (price, price2) = order_prices(params, "close", close_type, pair_decimals)?;
+            format!(
+                "close position @ {}",
+                describe_type(close_type, &price, &price2)
+            )
+        }
+        None => String::new(),
+    };
+
+    let altname = pair_info["altname"].as_str().unwrap();
+    let mut description = format!(
+        "{side} {vol} {altname} @ {}",
+        describe_type(ordertype, &price, &price2)
+    );
+    if let Some(leverage) = leverage {
+        description.push_str(&format!(" with {leverage}:1 leverage"));
+    }
+    let mut order = json!({
+        "refid": null,
+        "userref": userref,
+        "status": "open",
+        "opentm": now(),
+        "starttm": starttm,
+        "expiretm": expiretm,
+        "descr": {
+            "pair": altname,
+            "type": side,
+            "ordertype": ordertype,
+            "price": price,
+            "price2": price2,
+            "leverage": leverage.map_or("none".to_string(), |leverage| format!("{leverage}:1")),
+            "order": description,
+            "close": close,
+        },
+        "vol": vol,
+        "vol_exec": format_decimal("0", lot_decimals)?,
+        "cost": "0.000000",
+        "fee": "0.000000",
+        "price": "0.000000",
+        "stopprice": "0.000000",
+        "limitprice": "0.000000",
+        "misc": "",
+        "oflags": oflags.join(","),
+    });
+    if let Some(cl_ord_id) = cl_ord_id {
+        order["cl_ord_id"] = json!(cl_ord_id);
+    }
+    Ok(order)
+}
+
+fn descr(order: &Value) -> Value {
+    let descr = &order["descr"];
+    match descr["close"].as_str() {
+        Some("") | None => json!({ "order": descr["order"] }),
+        Some(close) => json!({ "order": descr["order"], "close": close }),
+    }
+}
+
+impl Book {
+    fn next_id(&mut self, prefix: char) -> String {
+        self.last_id += 1;
+        format!("{prefix}FAKEX-SQUID-{:06}", self.last_id)
+    }
+
+    fn close(&mut self, txid: &str, reason: &str) {
+        self.requests.remove(txid);
+        if let Some(mut order) = self.open.shift_remove(txid) {
+            order["status"] = json!("canceled");
+            order["reason"] = json!(reason);
+            order["closetm"] = json!(now());
+            self.closed.insert(txid.to_string(), order);
+        }
+    }
+
+    /// Fires the dead man's switch, if its time has come. Called before every private request.
+    pub(super) fn check_cancel_all_after(&mut self) {
+        if self.cancel_all_at.is_some_and(|at| at <= now()) {
+            self.cancel_all_at = None;
+            self.close_all();
+        }
+    }
+
+    pub(super) fn open_orders(&self, params: &Query) -> Outcome {
+        let open: serde_json::Map<String, Value> = self
+            .open
+            .iter()
+            .filter(|(_, order)| matches_userref(order, params))
+            .map(|(txid, order)| (txid.clone(), order.clone()))
+            .collect();
+        Ok(json!({ "open": open }))
+    }
+
+    pub(super) fn closed(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.closed.iter()
+    }
+
+    /// Open or closed order placed on the fake account.
+    pub(super) fn find(&self, txid: &str) -> Option<&Value> {
+        self.open.get(txid).or_else(|| self.closed.get(txid))
+    }
+
+    /// Stores the order, market orders are filled at once.
+    fn place(&mut self, txid: String, mut order: Value, params: &Query) {
+        if matches!(
+            order["descr"]["ordertype"].as_str(),
+            Some("market" | "settle-position")
+        ) {
+            order["status"] = json!("closed");
+            order["vol_exec"] = order["vol"].clone();
+            order["closetm"] = json!(now());
+            self.closed.insert(txid, order);
+        } else {
+            self.requests.insert(txid.clone(), params.clone());
+            self.open.insert(txid, order);
+        }
+    }
+
+    pub(super) fn add_order(&mut self, params: &Query) -> Outcome {
+        let order = build_order(params)?;
+        if is_validation(params) {
+            return Ok(json!({ "descr": descr(&order) }));
+        }
+        let txid = self.next_id('O');
+        let result = json!({ "descr": descr(&order), "txid": [txid] });
+        self.place(txid, order, params);
+        Ok(result)
+    }
+
+    /// Orders of the batch are placed for the same pair, each of them is accepted or rejected on its own.
+    pub(super) fn add_order_batch(&mut self, params: &Query) -> Outcome {
+        let orders = nested_params(params, "orders")?;
+        if !(MIN_BATCH_ORDERS..=MAX_BATCH_ORDERS).contains(&orders.len()) {
+            return Err(INVALID_ARGUMENTS);
+        }
+        let pair = params.get("pair").ok_or(INVALID_ARGUMENTS)?;
+        let results: Vec<Value> = orders
+            .into_iter()
+            .map(|mut order| {
+                order.insert("pair".to_string(), pair.clone());
+                if let Some(validate) = params.get("validate") {
+                    order.insert("validate".to_string(), validate.clone());
+                }
+                match self.add_order(&order) {
+                    Ok(mut placed) => {
+                        if let Some(txid) = placed.get_mut("txid") {
+                            *txid = txid[0].take();
+                        }
+                        placed
+                    }
+                    Err(e) => json!({ "error": e }),
+                }
+            })
+            .collect();
+        Ok(json!({ "orders": results }))
+    }
+
+    pub(super) fn cancel_order_batch(&mut self, params: &Query) -> Outcome {
+        let ids = indexed_params(params, "orders")?;
+        if ids.is_empty() || ids.len() > MAX_BATCH_CANCELS {
+            return Err(INVALID_ARGUMENTS);
+        }
+        let mut count = 0;
+        for id in ids {
+            let query = Query::from([("txid".to_string(), id)]);
+            // Unknown orders are skipped, only cancelled ones are counted.
+            if let Ok(cancelled) = self.cancel_order(&query) {
+                count += cancelled["count"].as_u64().unwrap();
+            }
+        }
+        Ok(json!({ "count": count }))
+    }
+
+    pub(super) fn edit_order(&mut self, params: &Query) -> Outcome {
+        let txid = params.get("txid").ok_or(INVALID_ARGUMENTS)?;
+        if !params.contains_key("pair") {
+            return Err(INVALID_ARGUMENTS);
+        }
+        let mut edited = self.requests.get(txid).ok_or(UNKNOWN_ORDER)?.clone();
+        for key in ["volume", "price", "price2", "userref", "oflags"] {
+            if let Some(val) = params.get(key) {
+                edited.insert(key.to_string(), val.clone());
+            }
+        }
+        let order = build_order(&edited)?;
+        let mut result = json!({
+            "status": "ok",
+            "descr": descr(&order),
+            "volume": order["vol"],
+            "price": order["descr"]["price"],
+            "price2": order["descr"]["price2"],
+        });
+        if is_validation(params) {
+            return Ok(result);
+        }
+        let txid = txid.clone();
+        self.close(&txid, "Order replaced");
+        let new_txid = self.next_id('O');
+        result["txid"] = json!(new_txid);
+        result["originaltxid"] = json!(txid);
+        result["orders_cancelled"] = json!(1);
+        self.place(new_txid, order, &edited);
+        Ok(result)
+    }
+
+    pub(super) fn amend_order(&mut self, params: &Query) -> Outcome {
+        let txid = params.get("txid").ok_or(INVALID_ARGUMENTS)?;
+        let original = self.open.get(txid).ok_or(UNKNOWN_ORDER)?;
+        let mut amended = self.requests[txid].clone();
+        for (key, param) in [("volume", "order_qty"), ("price", "limit_price")] {
+            if let Some(val) = params.get(param) {
+                amended.insert(key.to_string(), val.clone());
+            }
+        }
+        let mut order = build_order(&amended)?;
+        // Amended order keeps its place in the book.
+        order["opentm"] = original["opentm"].clone();
+        order["starttm"] = original["starttm"].clone();
+        order["expiretm"] = original["expiretm"].clone();
+        self.open.insert(txid.clone(), order);
+        self.requests.insert(txid.clone(), amended);
+        Ok(json!({ "amend_id": self.next_id('T') }))
+    }
+
+    /// Cancels order by txid or cl_ord_id, or all orders with userref, if the number is given instead of txid.
+    pub(super) fn cancel_order(&mut self, params: &Query) -> Outcome {
+        if let Some(cl_ord_id) = params.get("cl_ord_id") {
+            let txid = self
+                .open
+                .iter()
+                .find(|(_, order)| order["cl_ord_id"] == **cl_ord_id)
+                .map(|(txid, _)| txid.clone())
+                .ok_or(UNKNOWN_ORDER)?;
+            self.close(&txid, "User requested");
+            return Ok(json!({ "count": 1, "pending": false }));
+        }
+        let id = params.get("txid").ok_or(INVALID_ARGUMENTS)?;
+        let txids: Vec<String> = match id.parse::<i64>() {
+            Ok(userref) => self
+                .open
+                .iter()
+                .filter(|(_, order)| order["userref"].as_i64() == Some(userref))
+                .map(|(txid, _)| txid.clone())
+                .collect(),
+            Err(_) if self.open.contains_key(id) => vec![id.clone()],
+            Err(_) => return Err(UNKNOWN_ORDER),
+        };
+        for txid in &txids {
+            self.close(txid, "User requested");
+        }
+        Ok(json!({ "count": txids.len(), "pending": false }))
+    }
+
+    /// Cancels every open order, returns number of them.
+    fn close_all(&mut self) -> usize {
+        let txids: Vec<String> = self.open.keys().cloned().collect();
+        for txid in &txids {
+            self.close(txid, "User requested");
+        }
+        txids.len()
+    }
+
+    pub(super) fn cancel_all(&mut self) -> Outcome {
+        Ok(json!({ "count": self.close_all() }))
+    }
+
+    pub(super) fn cancel_all_orders_after(&mut self, params: &Query) -> Outcome {
+        let timeout: u32 = params
+            .get("timeout")
+            .ok_or(INVALID_ARGUMENTS)?
+            .parse()
+            .map_err(|_| INVALID_ARGUMENTS)?;
+        let current = now_utc();
+        let trigger_time = if timeout == 0 {
+            self.cancel_all_at = None;
+            "0".to_string()
+        } else {
+            let trigger = current + chrono::Duration::seconds(timeout.into());
+            self.cancel_all_at = Some(trigger.timestamp_millis() as f64 / 1000.0);
+            trigger.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+        };
+        Ok(json!({
+            "currentTime": current.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            "triggerTime": trigger_time,
+        }))
+    }
+}
+
+pub(super) fn matches_userref(order: &Value, params: &Query) -> bool {
+    params
+        .get("userref")
+        .is_none_or(|userref| userref.parse().ok() == order["userref"].as_i64())
+}
@@ -0,0 +1,261 @@
+use serde_json::{json, Map, Value};
+
+use super::{
+    error, number_param, orders, public, result, Exchange, Outcome, Query, INVALID_ARGUMENTS,
+    UNKNOWN_ASSET, UNKNOWN_METHOD,
+};
+
+const INVALID_ORDER: &str = "EOrder:Invalid order";
+
+const TRADE_TYPES: [&str; 6] = [
+    "all",
+    "any position",
+    "closed position",
+    "closing position",
+    "no position",
+    "",
+];
+const LEDGER_TYPES: [&str; 10] = [
+    "all",
+    "deposit",
+    "withdrawal",
+    "trade",
+    "margin",
+    "rollover",
+    "credit",
+    "transfer",
+    "settled",
+    "staking",
+];
+
+fn fixture(name: &str) -> Value {
+    let text = match name {
+        "balance" => include_str!("../fixtures/private/balance.json"),
+        "balance_ex" => include_str!("../fixtures/private/balance_ex.json"),
+        "trade_balance" => include_str!("../fixtures/private/trade_balance.json"),
+        "closed_orders" => include_str!("../fixtures/private/closed_orders.json"),
+        "trades_history" => include_str!("../fixtures/private/trades_history.json"),
+        "open_positions" => include_str!("../fixtures/private/open_positions.json"),
+        "ledgers" => include_str!("../fixtures/private/ledgers.json"),
+        "trade_volume" => include_str!("../fixtures/private/trade_volume.json"),
+        _ => panic!("There is no {name} fixture"),
+    };
+    serde_json::from_str(text).unwrap_or_else(|e| panic!("Fixture {name} is malformed: {e}"))
+}
+
+/// Full response body of private endpoint, the request is already authenticated.
+pub(super) fn respond(endpoint: &str, params: &Query, exchange: &mut Exchange) -> Value {
+    let outcome = match endpoint {
+        "Balance" => Ok(fixture("balance")),
+        "BalanceEx" => Ok(fixture("balance_ex")),
+        "TradeBalance" => trade_balance(params),
+        "OpenOrders" => exchange.orders.open_orders(params),
+        "ClosedOrders" => closed_orders(params, exchange),
+        "QueryOrders" => query_orders(params, exchange),
+        "TradesHistory" => trades_history(params),
+        "QueryTrades" => query_trades(params),
+        "OpenPositions" => open_positions(params),
+        "Ledgers" => ledgers(params),
+        "QueryLedgers" => query_ledgers(params),
+        "TradeVolume" => trade_volume(params),
+        "AddOrder" => exchange.orders.add_order(params),
+        "AddOrderBatch" => exchange.orders.add_order_batch(params),
+        "CancelOrderBatch" => exchange.orders.cancel_order_batch(params),
+        "EditOrder" => exchange.orders.edit_order(params),
+        "AmendOrder" => exchange.orders.amend_order(params),
+        "CancelOrder" => exchange.orders.cancel_order(params),
+        "CancelAll" => exchange.orders.cancel_all(),
+        "CancelAllOrdersAfter" => exchange.orders.cancel_all_orders_after(params),
+        _ => Err(UNKNOWN_METHOD),
+    };
+    outcome.map_or_else(error, result)
+}
+
+fn trade_balance(params: &Query) -> Outcome {
+    let assets = public::fixture("assets");
+    public::select_keys(params, "asset", assets.as_object().unwrap(), UNKNOWN_ASSET)?;
+    Ok(fixture("trade_balance"))
+}
+
+/// Entries with timestamp in `time_field` between optional "start" and "end" parameters.
+fn in_time_range(
+    entries: impl IntoIterator<Item = (String, Value)>,
+    params: &Query,
+    time_field: &str,
+) -> Result<Map<String, Value>, &'static str> {
+    let start = number_param(params, "start")?.map_or(f64::MIN, |start| start as f64);
+    let end = number_param(params, "end")?.map_or(f64::MAX, |end| end as f64);
+    if start > end {
+        return Err(INVALID_ARGUMENTS);
+    }
+    Ok(entries
+        .into_iter()
+        .filter(|(_, entry)| {
+            let time = entry[time_field].as_f64().unwrap();
+            start <= time && time <= end
+        })
+        .collect())
+}
+
+/// Result with selected entries under `key` and their count.
+fn counted(key: &str, entries: Map<String, Value>) -> Value {
+    json!({ "count": entries.len(), key: entries })
+}
+
+fn closed_orders(params: &Query, exchange: &Exchange) -> Outcome {
+    let time_field = match params.get("closetime").map(String::as_str) {
+        None | Some("both") | Some("close") => "closetm",
+        Some("open") => "opentm",
+        Some(_) => return Err(INVALID_ARGUMENTS),
+    };
+    let mut orders = fixture("closed_orders").as_object().unwrap().clone();
+    for (txid, order) in exchange.orders.closed() {
+        orders.insert(txid.clone(), order.clone());
+    }
+    let orders = orders
+        .into_iter()
+        .filter(|(_, order)| orders::matches_userref(order, params));
+    Ok(counted(
+        "closed",
+        in_time_range(orders, params, time_field)?,
+    ))
+}
+
+/// Comma separated ids from required parameter, at most 50 of them like the exchange allows.
+fn ids(params: &Query, name: &str) -> Result<Vec<String>, &'static str> {
+    let ids: Vec<String> = params
+        .get(name)
+        .ok_or(INVALID_ARGUMENTS)?
+        .split(',')
+        .map(str::to_string)
+        .collect();
+    if ids.len() > 50 || ids.iter().any(String::is_empty) {
+        return Err(INVALID_ARGUMENTS);
+    }
+    Ok(ids)
+}
+
+fn query_orders(params: &Query, exchange: &Exchange) -> Outcome {
+    let closed = fixture("closed_orders");
+    let mut selected = Map::new();
+    for txid in ids(params, "txid")? {
+        let order = exchange
+            .orders
+            .find(&txid)
+            .or_else(|| closed.get(&txid))
+            .ok_or(INVALID_ORDER)?;
+        selected.insert(txid, order.clone());
+    }
+    Ok(Value::Object(selected))
+}
+
+fn trades_history(params: &Query) -> Outcome {
+    if let Some(trade_type) = params.get("type") {
+        if !TRADE_TYPES.contains(&trade_type.as_str()) {
+            return Err(INVALID_ARGUMENTS);
+        }
+    }
+    let trades = fixture("trades_history").as_object().unwrap().clone();
+    Ok(counted("trades", in_time_range(trades, params, "time")?))
+}
+
+/// Entries of the fixture with requested ids, the unknown id is reported with `unknown` error.
+fn query_fixture(name: &str, params: &Query, param: &str, unknown: &'static str) -> Outcome {
+    let entries = fixture(name);
+    let mut selected = Map::new();
+    for id in ids(params, param)? {
+        let entry = entries.get(&id).ok_or(unknown)?;
+        selected.insert(id, entry.clone());
+    }
+    Ok(Value::Object(selected))
+}
+
+fn query_trades(params: &Query) -> Outcome {
+    query_fixture("trades_history", params, "txid", INVALID_ARGUMENTS)
+}
+
+fn open_positions(params: &Query) -> Outcome {
+    let mut positions = fixture("open_positions");
+    if let Some(txid) = params.get("txid") {
+        let requested: Vec<&str> = txid.split(',').collect();
+        positions
+            .as_object_mut()
+            .unwrap()
+            .retain(|id, _| requested.contains(&id.as_str()));
+    }
+    if params.get("docalcs").map(String::as_str) != Some("true") {
+        for position in positions.as_object_mut().unwrap().values_mut() {
+            let position = position.as_object_mut().unwrap();
+            position.remove("value");
+            position.remove("net");
+        }
+    }
+    Ok(positions)
+}
+
+fn ledgers(params: &Query) -> Outcome {
+    let ledger_type = params.get("type").map_or("all", String::as_str);
+    if !LEDGER_TYPES.contains(&ledger_type) {
+        return Err(INVALID_ARGUMENTS);
+    }
+    let assets: Option<Vec<&str>> = params
+        .get("asset")
+        .filter(|asset| *asset != "all")
+        .map(|asset| asset.split(',').collect());
+    let entries = fixture("ledgers")
+        .as_object()
+        .unwrap()
+        .clone()
+        .into_iter()
+        .filter(|(_, entry)| ledger_type == "all" || entry["type"] == ledger_type)
+        .filter(|(_, entry)| {
+            assets
+                .as_ref()
+                .is_none_or(|assets| assets.contains(&entry["asset"].as_str().unwrap()))
+        });
+    Ok(counted("ledger", in_time_range(entries, params, "time")?))
+}
+
+fn query_ledgers(params: &Query) -> Outcome {
+    query_fixture("ledgers", params, "id", INVALID_ARGUMENTS)
+}
+
+/// Fee tier of 30 days volume from the asset pair fee schedule, e.g. [[0, 0.26], [50000, 0.24], ...].
+fn fee_tier(schedule: &Value, volume: f64) -> Value {
+    let tiers = schedule.as_array().unwrap();
+    let current = tiers
+        .iter()
+        .rposition(|tier| tier[0].as_f64().unwrap() <= volume)
+        .unwrap_or_default();
+    let fee = |tier: &Value| format!("{:.4}", tier[1].as_f64().unwrap());
+    let next = tiers.get(current + 1);
+    json!({
+        "fee": fee(&tiers[current]),
+        "minfee": fee(tiers.last().unwrap()),
+        "maxfee": fee(&tiers[0]),
+        "nextfee": next.map(fee),
+        "nextvolume": next.map(|tier| format!("{:.4}", tier[0].as_f64().unwrap())),
+        "tiervolume": format!("{:.4}", tiers[current][0].as_f64().unwrap()),
+    })
+}
+
+fn trade_volume(params: &Query) -> Outcome {
+    let mut volume = fixture("trade_volume");
+    if !params.contains_key("pair") {
+        return Ok(volume);
+    }
+    let current: f64 = volume["volume"].as_str().unwrap().parse().unwrap();
+    let asset_pairs = public::fixture("asset_pairs");
+    let mut fees = Map::new();
+    let mut fees_maker = Map::new();
+    for pair in public::pairs(params, true)? {
+        fees.insert(pair.clone(), fee_tier(&asset_pairs[&pair]["fees"], current));
+        fees_maker.insert(
+            pair.clone(),
+            fee_tier(&asset_pairs[&pair]["fees_maker"], current),
+        );
+    }
+    volume["fees"] = Value::Object(fees);
+    volume["fees_maker"] = Value::Object(fees_maker);
+    Ok(volume)
+}
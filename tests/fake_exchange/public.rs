@@ -0,0 +1,216 @@
+use serde_json::{json, Map, Value};
+
+use super::{
+    error, now_utc, number_param, result, Outcome, Query, INVALID_ARGUMENTS, UNKNOWN_ASSET,
+    UNKNOWN_METHOD, UNKNOWN_PAIR,
+};
+
+const OHLC_INTERVALS: [i64; 9] = [1, 5, 15, 30, 60, 240, 1440, 10080, 21600];
+const MAX_DEPTH_COUNT: i64 = 500;
+
+pub(super) fn fixture(name: &str) -> Value {
+    let text = match name {
+        "system_status" => include_str!("../fixtures/public/system_status.json"),
+        "assets" => include_str!("../fixtures/public/assets.json"),
+        "asset_pairs" => include_str!("../fixtures/public/asset_pairs.json"),
+        "ticker" => include_str!("../fixtures/public/ticker.json"),
+        "ohlc" => include_str!("../fixtures/public/ohlc.json"),
+        "depth" => include_str!("../fixtures/public/depth.json"),
+        "trades" => include_str!("../fixtures/public/trades.json"),
+        "spread" => include_str!("../fixtures/public/spread.json"),
+        _ => panic!("There is no {name} fixture"),
+    };
+    serde_json::from_str(text).unwrap_or_else(|e| panic!("Fixture {name} is malformed: {e}"))
+}
+
+/// Full response body of public endpoint.
+pub fn respond(endpoint: &str, query: &Query) -> Value {
+    let outcome = match endpoint {
+        "Time" => Ok(time()),
+        "SystemStatus" => Ok(fixture("system_status")),
+        "Assets" => assets(query),
+        "AssetPairs" => asset_pairs(query),
+        "Ticker" => ticker(query),
+        "OHLC" => ohlc(query),
+        "Depth" => depth(query),
+        "Trades" => trades(query),
+        "Spread" => spread(query),
+        _ => Err(UNKNOWN_METHOD),
+    };
+    outcome.map_or_else(error, result)
+}
+
+fn time() -> Value {
+    let now = now_utc();
+    json!({
+        "unixtime": now.timestamp(),
+        "rfc1123": now.format("%a, %d %b %y %T %z").to_string(),
+    })
+}
+
+/// Keys of the fixture entries selected by comma separated list in `param`.
+/// Entries can be given by their key or altname, all of them are selected when the parameter is missing.
+pub(super) fn select_keys(
+    query: &Query,
+    param: &str,
+    names: &Map<String, Value>,
+    unknown: &'static str,
+) -> Result<Vec<String>, &'static str> {
+    let Some(requested) = query.get(param) else {
+        return Ok(names.keys().cloned().collect());
+    };
+    requested
+        .split(',')
+        .map(|name| {
+            names
+                .iter()
+                .find(|(key, info)| {
+                    key.eq_ignore_ascii_case(name)
+                        || info["altname"]
+                            .as_str()
+                            .is_some_and(|altname| altname.eq_ignore_ascii_case(name))
+                })
+                .map(|(key, _)| key.clone())
+                .ok_or(unknown)
+        })
+        .collect()
+}
+
+/// Canonical names of requested pairs, e.g. "xbtusd" -> "XXBTZUSD".
+pub(super) fn pairs(query: &Query, required: bool) -> Result<Vec<String>, &'static str> {
+    if required && !query.contains_key("pair") {
+        return Err(INVALID_ARGUMENTS);
+    }
+    let asset_pairs = fixture("asset_pairs");
+    select_keys(
+        query,
+        "pair",
+        asset_pairs.as_object().unwrap(),
+        UNKNOWN_PAIR,
+    )
+}
+
+fn pick(fixture: &Value, keys: &[String]) -> Map<String, Value> {
+    keys.iter()
+        .map(|key| (key.clone(), fixture[key].clone()))
+        .collect()
+}
+
+fn assets(query: &Query) -> Outcome {
+    if query
+        .get("aclass")
+        .is_some_and(|aclass| aclass != "currency")
+    {
+        return Err(INVALID_ARGUMENTS);
+    }
+    let assets = fixture("assets");
+    let keys = select_keys(query, "asset", assets.as_object().unwrap(), UNKNOWN_ASSET)?;
+    Ok(Value::Object(pick(&assets, &keys)))
+}
+
+fn asset_pairs(query: &Query) -> Outcome {
+    let fields: &[&str] = match query.get("info").map(String::as_str) {
+        None | Some("info") => &[],
+        Some("leverage") => &["leverage_buy", "leverage_sell"],
+        Some("fees") => &["fee_volume_currency", "fees", "fees_maker"],
+        Some("margin") => &["margin_call", "margin_stop"],
+        Some(_) => return Err(INVALID_ARGUMENTS),
+    };
+    let keys = pairs(query, false)?;
+    let mut selected = pick(&fixture("asset_pairs"), &keys);
+    if !fields.is_empty() {
+        for info in selected.values_mut() {
+            let mut limited: Map<String, Value> = fields
+                .iter()
+                .map(|field| (field.to_string(), info[*field].clone()))
+                .collect();
+            // Margin info is returned with margin_level key, see TradingPairMargin.
+            if let Some(margin_stop) = limited.remove("margin_stop") {
+                limited.insert("margin_level".to_string(), margin_stop);
+            }
+            *info = Value::Object(limited);
+        }
+    }
+    Ok(Value::Object(selected))
+}
+
+fn ticker(query: &Query) -> Outcome {
+    let keys = pairs(query, false)?;
+    Ok(Value::Object(pick(&fixture("ticker"), &keys)))
+}
+
+/// Entries of every requested pair newer than `since`, together with `last` cursor.
+/// `time_index` is position of the timestamp in the entry.
+fn history(
+    fixture: &Value,
+    keys: &[String],
+    since: Option<f64>,
+    count: Option<usize>,
+    time_index: usize,
+) -> (Map<String, Value>, f64) {
+    let mut last = since.unwrap_or_default();
+    let mut selected = Map::new();
+    for key in keys {
+        let entries: Vec<Value> = fixture[key]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|entry| entry[time_index].as_f64().unwrap() > since.unwrap_or_default())
+            .take(count.unwrap_or(usize::MAX))
+            .cloned()
+            .collect();
+        if let Some(newest) = entries.last() {
+            last = last.max(newest[time_index].as_f64().unwrap());
+        }
+        selected.insert(key.clone(), Value::Array(entries));
+    }
+    (selected, last)
+}
+
+fn ohlc(query: &Query) -> Outcome {
+    let keys = pairs(query, true)?;
+    let interval = number_param(query, "interval")?.unwrap_or(1);
+    if !OHLC_INTERVALS.contains(&interval) {
+        return Err(INVALID_ARGUMENTS);
+    }
+    let since = number_param(query, "since")?.map(|since| since as f64);
+    let (mut selected, last) = history(&fixture("ohlc"), &keys, since, None, 0);
+    selected.insert("last".to_string(), json!(last as i64));
+    Ok(Value::Object(selected))
+}
+
+fn depth(query: &Query) -> Outcome {
+    let keys = pairs(query, true)?;
+    let count = number_param(query, "count")?.unwrap_or(100);
+    if !(1..=MAX_DEPTH_COUNT).contains(&count) {
+        return Err(INVALID_ARGUMENTS);
+    }
+    let mut selected = pick(&fixture("depth"), &keys);
+    for book in selected.values_mut() {
+        for side in ["asks", "bids"] {
+            book[side].as_array_mut().unwrap().truncate(count as usize);
+        }
+    }
+    Ok(Value::Object(selected))
+}
+
+fn trades(query: &Query) -> Outcome {
+    let keys = pairs(query, true)?;
+    let since = number_param(query, "since")?.map(|since| since as f64);
+    let count = number_param(query, "count")?
+        .map(|count| usize::try_from(count).map_err(|_| INVALID_ARGUMENTS))
+        .transpose()?;
+    let (mut selected, last) = history(&fixture("trades"), &keys, since, count, 2);
+    // Cursor of trades is a nanosecond timestamp sent as string.
+    let last_ns = (last * 1e9) as i64;
+    selected.insert("last".to_string(), json!(last_ns.to_string()));
+    Ok(Value::Object(selected))
+}
+
+fn spread(query: &Query) -> Outcome {
+    let keys = pairs(query, true)?;
+    let since = number_param(query, "since")?.map(|since| since as f64);
+    let (mut selected, last) = history(&fixture("spread"), &keys, since, None, 0);
+    selected.insert("last".to_string(), json!(last as i64));
+    Ok(Value::Object(selected))
+}
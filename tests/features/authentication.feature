@@ -0,0 +1,43 @@
+@serial @private @permission-query-funds
+Feature: Verify if server rejects private requests with broken authentication
+
+    Scenario: Request with body changed after signing should be rejected
+        Given User has account, API KEY, and API secret
+        When TradeBalance request is sent with body changed after signing
+        Then Response body contains "EAPI:Invalid signature" error
+
+    Scenario: Request signed for other path should be rejected
+        Given User has account, API KEY, and API secret
+        When TradeBalance request signed for Balance is sent
+        Then Response body contains "EAPI:Invalid signature" error
+
+    # Accounts with nonce window allow reused and lower nonces, the API key should not have it.
+    Scenario: Request sent again with the same nonce should be rejected
+        Given User has account, API KEY, and API secret
+        When TradeBalance request is sent with correct signature
+        Then Response body contains TradeBalance result
+        When The same request is sent again
+        Then Response body contains "EAPI:Invalid nonce" error
+
+    Scenario: Request with nonce lower than the last one should be rejected
+        Given User has account, API KEY, and API secret
+        When TradeBalance request is sent with correct signature
+        Then Response body contains TradeBalance result
+        When TradeBalance request is sent with nonce lower than the last one
+        Then Response body contains "EAPI:Invalid nonce" error
+
+    Scenario: Request signed with other secret should be rejected
+        Given User has account, API KEY, and API secret
+        When TradeBalance request is sent signed with other secret
+        Then Response body contains "EAPI:Invalid signature" error
+
+    Scenario: Request without API key should be rejected
+        Given User has account, API KEY, and API secret
+        When TradeBalance request is sent without API-Key header
+        Then Response body contains "EAPI:Invalid key" error
+
+    # Such secret cannot be turned into a signature, so the request never reaches the server.
+    Scenario: Request should not be signed with secret, which is not base64
+        Given User has account, API KEY, and API secret
+        When TradeBalance request is signed with secret "not base64!"
+        Then Request is not signed, because the secret is not valid base64
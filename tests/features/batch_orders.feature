@@ -0,0 +1,39 @@
+@serial @trading @permission-modify-orders @permission-query-open-orders
+Feature: Verify if user can place and cancel batch of orders in one request
+
+    @real-orders
+    Scenario: Batch of orders should be placed and cancelled together
+        Given User has account, API KEY, and API secret
+        And User has no open orders
+        When User submits batch of orders for "ETHXBT"
+            | name   | type | ordertype | volume | price |
+            | lower  | sell | limit     | 0.011  | 0.5   |
+            | higher | sell | limit     | 0.012  | 0.6   |
+        Then Response body contains AddOrderBatch result
+        And Order "lower" is placed
+        And Order "higher" is placed
+        And Open orders contain order "higher"
+        When Batch of orders "lower, higher" is cancelled
+        Then Response body contains CancelOrderBatch result
+        And Cancelled order count is 2
+        And Open orders omit order "lower"
+
+    Scenario: Invalid order in batch should be reported with its own error
+        Given User has account, API KEY, and API secret
+        And User has no open orders
+        When User submits batch of orders for "ETHXBT"
+            | name  | type | ordertype | volume | price |
+            | valid | sell | limit     | 0.011  | 0.5   |
+            | tiny  | sell | limit     | 0.0001 | 0.5   |
+        Then Order "valid" is placed
+        And Order "tiny" is rejected with "EOrder:Order minimum not met"
+
+    Scenario Outline: Batch with number of orders out of limits should be rejected
+        Given User has account, API KEY, and API secret
+        When User submits batch of <count> copies of some order
+        Then Response body contains "EGeneral:Invalid arguments" error
+
+        Examples:
+            | count |
+            | 1     |
+            | 16    |
@@ -0,0 +1,28 @@
+@serial @private @permission-query-funds
+Feature: Verify if server can respond with balances of user account
+
+    Scenario: Server should return balance of every asset
+        Given User has account, API KEY, and API secret
+        When Private request for Balance is send
+        Then Response body contains Balance result
+        And the response arrived within 1500 ms
+
+    Scenario: Server should return extended balance with amounts on hold
+        Given User has account, API KEY, and API secret
+        When Private request for BalanceEx is send
+        Then Response body contains BalanceEx result
+
+    Scenario Outline: Server should return trade balance in requested asset
+        Given User has account, API KEY, and API secret
+        When Private request for TradeBalance is send with "<query>"
+        Then Response body contains TradeBalance result
+
+        Examples:
+            | query      |
+            | asset=ZUSD |
+            | asset=XBT  |
+
+    Scenario: Server should reject trade balance in unknown asset
+        Given User has account, API KEY, and API secret
+        When Private request for TradeBalance is send with "asset=NOTANASSET"
+        Then Response body contains "EQuery:Unknown asset" error
@@ -0,0 +1,39 @@
+@serial @private @permission-query-closed-orders
+Feature: Verify if server can respond with closed orders of user
+
+    Scenario: Server should return list of closed orders
+        Given User has account, API KEY, and API secret
+        When Private request for ClosedOrders is send
+        Then Response body contains ClosedOrders result
+        And Result count matches number of entries
+        And the response arrived within 1500 ms
+
+    Scenario Outline: Server should return closed orders filtered by time
+        Given User has account, API KEY, and API secret
+        When Private request for ClosedOrders is send with "<query>"
+        Then Response body contains ClosedOrders result
+        And Result count matches number of entries
+
+        Examples:
+            | query                                 |
+            | closetime=open                        |
+            | closetime=close&start=1665990000      |
+            | closetime=both&start=0&end=1665999999 |
+
+    Scenario: Server should reject invalid closetime
+        Given User has account, API KEY, and API secret
+        When Private request for ClosedOrders is send with "closetime=yesterday"
+        Then Response body contains "EGeneral:Invalid arguments" error
+
+    Scenario: Server should return closed orders queried by their ids
+        Given User has account, API KEY, and API secret
+        When Private request for ClosedOrders is send
+        And Ids from the last result are remembered
+        And Private request for QueryOrders is send with remembered ids as txid
+        Then Response body contains QueryOrders result
+        And Result has entries for remembered ids
+
+    Scenario: Server should reject query for unknown order
+        Given User has account, API KEY, and API secret
+        When Private request for QueryOrders is send with "txid=OXXXXX-XXXXX-XXXXXX"
+        Then Response body contains "EOrder:Invalid order" error
@@ -0,0 +1,35 @@
+@serial @private @permission-query-ledger
+Feature: Verify if server can respond with ledger entries of user
+
+    Scenario: Server should return ledger entries
+        Given User has account, API KEY, and API secret
+        When Private request for Ledgers is send
+        Then Response body contains Ledgers result
+        And Result count matches number of entries
+        And the response arrived within 1500 ms
+
+    Scenario Outline: Server should return ledger entries filtered by type and asset
+        Given User has account, API KEY, and API secret
+        When Private request for Ledgers is send with "<query>"
+        Then Response body contains Ledgers result
+        And Every result entry has <field> "<value>"
+
+        Examples:
+            | query               | field | value   |
+            | type=trade          | type  | trade   |
+            | type=deposit        | type  | deposit |
+            | asset=ZUSD          | asset | ZUSD    |
+            | type=all&asset=XXBT | asset | XXBT    |
+
+    Scenario: Server should return ledger entries queried by their ids
+        Given User has account, API KEY, and API secret
+        When Private request for Ledgers is send
+        And Ids from the last result are remembered
+        And Private request for QueryLedgers is send with remembered ids as id
+        Then Response body contains QueryLedgers result
+        And Result has entries for remembered ids
+
+    Scenario: Server should reject invalid ledger type
+        Given User has account, API KEY, and API secret
+        When Private request for Ledgers is send with "type=lottery"
+        Then Response body contains "EGeneral:Invalid arguments" error
@@ -0,0 +1,14 @@
+@serial @private @permission-query-open-orders
+Feature: Verify if server can respond with open margin positions of user
+
+    Scenario: Server should return open positions
+        Given User has account, API KEY, and API secret
+        When Private request for OpenPositions is send
+        Then Response body contains OpenPositions result
+        And Open positions are returned without calculated value and net
+
+    Scenario: Server should return open positions with profit and loss calculations
+        Given User has account, API KEY, and API secret
+        When Private request for OpenPositions is send with "docalcs=true"
+        Then Response body contains OpenPositions result
+        And Open positions are returned with calculated value and net
@@ -0,0 +1,19 @@
+@serial @private @permission-query-funds
+Feature: Verify if server can respond with trade volume of user
+
+    Scenario: Server should return 30 day trade volume
+        Given User has account, API KEY, and API secret
+        When Private request for TradeVolume is send
+        Then Response body contains TradeVolume result
+        And the response arrived within 1500 ms
+
+    Scenario: Server should return fee tiers for requested pair
+        Given User has account, API KEY, and API secret
+        When Private request for TradeVolume is send with "pair=XBTUSD"
+        Then Response body contains TradeVolume result
+        And Result has taker and maker fee tiers for "XXBTZUSD"
+
+    Scenario: Server should reject unknown pair
+        Given User has account, API KEY, and API secret
+        When Private request for TradeVolume is send with "pair=NOTAPAIR"
+        Then Response body contains "EQuery:Unknown asset pair" error
@@ -0,0 +1,22 @@
+@serial @private @permission-query-closed-orders
+Feature: Verify if server can respond with trades history of user
+
+    Scenario: Server should return trades history
+        Given User has account, API KEY, and API secret
+        When Private request for TradesHistory is send
+        Then Response body contains TradesHistory result
+        And Result count matches number of entries
+        And the response arrived within 1500 ms
+
+    Scenario: Server should return trades queried by their ids
+        Given User has account, API KEY, and API secret
+        When Private request for TradesHistory is send with "type=all"
+        And Ids from the last result are remembered
+        And Private request for QueryTrades is send with remembered ids as txid
+        Then Response body contains QueryTrades result
+        And Result has entries for remembered ids
+
+    Scenario: Server should reject invalid trade type
+        Given User has account, API KEY, and API secret
+        When Private request for TradesHistory is send with "type=lottery"
+        Then Response body contains "EGeneral:Invalid arguments" error
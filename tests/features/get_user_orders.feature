@@ -1,3 +1,4 @@
+@serial @trading @permission-modify-orders @permission-query-open-orders
 Feature: Verify if server can respond with list of user orders
 
     Scenario: Server should return response with list of user orders
@@ -6,6 +7,13 @@ Feature: Verify if server can respond with list of user orders
         And User has no open orders
         When Request for list of orders is send
         Then There are not any orders in response body
+        And the response arrived within 1500 ms
         When User add some order
         And Request for list of orders is send
-        Then There is description of one order in response body
\ No newline at end of file
+        Then There is description of one order in response body
+
+    # Runs after the previous scenario, which leaves its order open.
+    Scenario: Orders left open by previous scenario should be cancelled after it
+        Given User has account, API KEY, and API secret
+        When Request for list of orders is send
+        Then There are not any orders in response body
@@ -0,0 +1,75 @@
+@serial @trading @permission-modify-orders @permission-query-open-orders
+Feature: Verify if user can edit, amend and cancel orders
+
+    @real-orders
+    Scenario Outline: Edited order should be replaced with new one
+        Given User has account, API KEY, and API secret
+        And User has no open orders
+        When User add some order
+        And Remembered order is edited with "<query>"
+        Then Response body contains EditOrder result
+        And Remembered order <field> equals <value>
+        And Remembered order has status "open"
+
+        Examples:
+            | query                    | field  | value |
+            | pair=ETHXBT&price=0.6    | price  | 0.6   |
+            | pair=ETHXBT&volume=0.02  | volume | 0.02  |
+
+    @real-orders
+    Scenario Outline: Amended order should keep its txid
+        Given User has account, API KEY, and API secret
+        And User has no open orders
+        When User add some order
+        And Remembered order is amended with "<query>"
+        Then Response body contains AmendOrder result
+        And Remembered order <field> equals <value>
+        And Open orders contain remembered order
+
+        Examples:
+            | query            | field  | value |
+            | limit_price=0.55 | price  | 0.55  |
+            | order_qty=0.02   | volume | 0.02  |
+
+    @real-orders
+    Scenario: Order should be cancelled by its txid
+        Given User has account, API KEY, and API secret
+        And User has no open orders
+        When User add some order
+        And Remembered order is cancelled
+        Then Response body contains CancelOrder result
+        And Cancelled order count is 1
+        And Remembered order has status "canceled"
+        And Open orders omit remembered order
+
+    @real-orders
+    Scenario: All orders with userref should be cancelled at once
+        Given User has account, API KEY, and API secret
+        And User has no open orders
+        When User add some order
+        And User add some order
+        And Orders of this run are cancelled
+        Then Cancelled order count is 2
+        And Open orders omit remembered order
+
+    Scenario: Cancelling unknown order should fail
+        Given User has account, API KEY, and API secret
+        When Order "OXXXXX-XXXXX-XXXXXX" is cancelled
+        Then Response body contains "EOrder:Unknown order" error
+
+    Scenario: Dead man's switch should be armed and disarmed
+        Given User has account, API KEY, and API secret
+        When Dead man's switch is armed for 60 seconds
+        Then Response body contains CancelAllOrdersAfter result
+        And Dead man's switch is reported as armed
+        When Dead man's switch is disarmed
+        Then Dead man's switch is reported as disarmed
+
+    @real-orders @cancels-all-orders
+    Scenario: Dead man's switch should cancel orders, when it is not disarmed in time
+        Given User has account, API KEY, and API secret
+        And User has no open orders
+        When User add some order
+        And Dead man's switch is armed for 1 seconds
+        And 3 seconds have passed
+        Then Open orders omit remembered order
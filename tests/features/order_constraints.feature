@@ -0,0 +1,51 @@
+@serial @private @permission-modify-orders
+Feature: Verify if orders are checked against constraints of the pair before they are sent
+
+    Scenario: Order within constraints of the pair should pass local check and be sent
+        Given User has account, API KEY, and API secret
+        And Constraints of pair "ETHXBT" are known
+        When Order to sell 0.011 ETHXBT at 0.5 is sent with local check
+        Then Order passes local check
+        And Response body contains AddOrder result
+
+    Scenario Outline: Order breaking <rule> should be rejected locally and by the server
+        Given User has account, API KEY, and API secret
+        And Constraints of pair "<pair>" are known
+        When Order to sell <volume> <pair> at <price> is sent with local check
+        Then Order is rejected locally by <rule> rule
+        When Checked order is validated by the server
+        Then Response body contains "<error>" error
+
+        Examples:
+            | pair   | volume       | price    | rule          | error                             |
+            | ETHXBT | 0.001        | 0.5      | ordermin      | EOrder:Order minimum not met      |
+            | ETHXBT | 0.0110000001 | 0.5      | lot_decimals  | EGeneral:Invalid arguments:volume |
+            | ETHXBT | 0.011        | 0.500001 | pair_decimals | EOrder:Invalid price              |
+            | XBTUSD | 0.0001       | 100      | costmin       | EOrder:Cost minimum not met       |
+
+    # Limit price of stop limit orders gives their cost, the same as of limit orders.
+    Scenario Outline: Cost of <ordertype> order under costmin should be rejected locally
+        Given Constraints of pair "XBTUSD" are known
+        When <ordertype> order to sell 0.0001 XBTUSD triggered at 100000 with limit 100 is checked against pair constraints
+        Then Order is rejected locally by costmin rule
+
+        Examples:
+            | ordertype         |
+            | stop-loss-limit   |
+            | take-profit-limit |
+
+    # Tick size of the listed pairs equals their price precision, so a coarser one is set to break only the tick size.
+    Scenario: Price off the tick size should be rejected locally
+        Given Constraints of pair "XBTUSD" are known with tick size 0.5
+        When Order to sell 0.01 XBTUSD at 100.2 is checked against pair constraints
+        Then Order is rejected locally by tick_size rule
+
+    Scenario Outline: Price on the tick size, or any price of pair with zero tick size, should pass local check
+        Given Constraints of pair "XBTUSD" are known with tick size <tick_size>
+        When Order to sell 0.01 XBTUSD at <price> is checked against pair constraints
+        Then Order passes local check
+
+        Examples:
+            | tick_size | price |
+            | 0.5       | 100.5 |
+            | 0         | 100.2 |
@@ -0,0 +1,56 @@
+@serial @private @permission-modify-orders
+Feature: Verify if server accepts every type of order with its options
+
+    Scenario Outline: Server should validate <ordertype> order with "<options>"
+        Given User has account, API KEY, and API secret
+        When Order <side> <ordertype> at "<price>" "<price2>" with "<options>" is validated
+        Then Response body contains AddOrder result
+        And Validated order description is "<description>"
+
+        Examples:
+            | side | ordertype           | price  | price2  | options                              | description                                                       |
+            | sell | market              |        |         |                                      | sell 0.01100000 ETHXBT @ market                                   |
+            | sell | market              |        |         | oflags=nompp,fcib                    | sell 0.01100000 ETHXBT @ market                                   |
+            | buy  | market              |        |         | oflags=viqc                          | buy 0.01100000 ETHXBT @ market                                    |
+            | sell | limit               | 0.5    |         |                                      | sell 0.01100000 ETHXBT @ limit 0.50000                            |
+            | sell | limit               | 0.5    |         | oflags=post,fciq                     | sell 0.01100000 ETHXBT @ limit 0.50000                            |
+            | sell | limit               | 0.5    |         | timeinforce=IOC                      | sell 0.01100000 ETHXBT @ limit 0.50000                            |
+            | sell | limit               | 0.5    |         | timeinforce=GTD&expiretm=+3600       | sell 0.01100000 ETHXBT @ limit 0.50000                            |
+            | sell | limit               | 0.5    |         | starttm=+60&expiretm=+3600           | sell 0.01100000 ETHXBT @ limit 0.50000                            |
+            | sell | limit               | 0.5    |         | timeinforce=GTD&expiretm=server+3600 | sell 0.01100000 ETHXBT @ limit 0.50000                            |
+            | sell | limit               | 0.5    |         | userref=35                           | sell 0.01100000 ETHXBT @ limit 0.50000                            |
+            | sell | limit               | 0.5    |         | cl_ord_id=squid-order-types          | sell 0.01100000 ETHXBT @ limit 0.50000                            |
+            | buy  | limit               | 0.01   |         | leverage=2                           | buy 0.01100000 ETHXBT @ limit 0.01000 with 2:1 leverage           |
+            | buy  | limit               | 0.01   |         | leverage=2&reduce_only=true          | buy 0.01100000 ETHXBT @ limit 0.01000 with 2:1 leverage           |
+            | sell | iceberg             | 0.5    |         | displayvol=0.01                      | sell 0.01100000 ETHXBT @ iceberg 0.50000                          |
+            | sell | stop-loss           | 0.01   |         |                                      | sell 0.01100000 ETHXBT @ stop loss 0.01000                        |
+            | sell | take-profit         | 0.5    |         |                                      | sell 0.01100000 ETHXBT @ take profit 0.50000                      |
+            | sell | stop-loss-limit     | 0.01   | 0.009   |                                      | sell 0.01100000 ETHXBT @ stop loss 0.01000 -> limit 0.00900       |
+            | sell | take-profit-limit   | 0.5    | 0.49    |                                      | sell 0.01100000 ETHXBT @ take profit 0.50000 -> limit 0.49000     |
+            | sell | trailing-stop       | +0.01  |         |                                      | sell 0.01100000 ETHXBT @ trailing stop +0.01000                   |
+            | sell | trailing-stop-limit | +0.01  | +0.001  |                                      | sell 0.01100000 ETHXBT @ trailing stop +0.01000 -> limit +0.00100 |
+            | sell | trailing-stop       | +5%    |         |                                      | sell 0.01100000 ETHXBT @ trailing stop +5.00%                     |
+            | sell | settle-position     |        |         | leverage=2                           | sell 0.01100000 ETHXBT @ settle position with 2:1 leverage        |
+
+    Scenario: Server should describe conditional close order
+        Given User has account, API KEY, and API secret
+        When Order buy limit at "0.01" "" with "close[ordertype]=stop-loss-limit&close[price]=0.009&close[price2]=0.008" is validated
+        Then Response body contains AddOrder result
+        And Validated order description is "buy 0.01100000 ETHXBT @ limit 0.01000"
+        And Validated close order description is "close position @ stop loss 0.00900 -> limit 0.00800"
+
+    Scenario Outline: Server should reject invalid combination "<options>" of <ordertype> order
+        Given User has account, API KEY, and API secret
+        When Order sell <ordertype> at "<price>" "" with "<options>" is validated
+        Then Response body contains "EGeneral:Invalid arguments" error
+
+        Examples:
+            | ordertype       | price | options                        |
+            | market          |       | oflags=post                    |
+            | limit           | 0.5   | oflags=fcib,fciq               |
+            | limit           | 0.5   | timeinforce=GTD                |
+            | limit           | 0.5   | reduce_only=true               |
+            | limit           | 0.5   | userref=35&cl_ord_id=squid-35  |
+            | limit           | 0.5   | leverage=7                     |
+            | trailing-stop   | 0.01  |                                |
+            | settle-position |       |                                |
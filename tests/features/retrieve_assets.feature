@@ -0,0 +1,23 @@
+@public
+Feature: Verifying if server returns asset informations
+
+    Scenario: Server should return informations about all assets
+        Given Squid website is responding
+        When Public request for Assets is send
+        Then Response body contains Assets result
+
+    Scenario: Server should return informations only about requested assets
+        Given Squid website is responding
+        When Public request for Assets is send with "asset=XBT,ETH"
+        Then Response body contains Assets result
+        And Result has only entries for "XXBT, XETH"
+
+    Scenario Outline: Server should return error on invalid assets request
+        Given Squid website is responding
+        When Public request for Assets is send with "<query>"
+        Then Response body contains "<error>" error
+
+        Examples:
+            | query          | error                      |
+            | asset=NOTACOIN | EQuery:Unknown asset       |
+            | aclass=stock   | EGeneral:Invalid arguments |
@@ -0,0 +1,25 @@
+@public
+Feature: Verifying if server returns OHLC data
+
+    Scenario: Server should return OHLC data of trading pair XBT/USD
+        Given Squid website is responding
+        When Public request for OHLC is send with "pair=XBTUSD"
+        Then Response body contains OHLC result
+        And Result has only entries for "XXBTZUSD"
+
+    Scenario: Server should return only OHLC data newer than requested
+        Given Squid website is responding
+        When Public request for OHLC is send with "pair=XBTUSD&interval=60&since=1666000000"
+        Then Response body contains OHLC result
+        And Result has only entries newer than 1666000000
+
+    Scenario Outline: Server should return error on invalid OHLC request
+        Given Squid website is responding
+        When Public request for OHLC is send with "<query>"
+        Then Response body contains "<error>" error
+
+        Examples:
+            | query                  | error                      |
+            | pair=NOTAPAIR          | EQuery:Unknown asset pair  |
+            | pair=XBTUSD&interval=7 | EGeneral:Invalid arguments |
+            | interval=60            | EGeneral:Invalid arguments |
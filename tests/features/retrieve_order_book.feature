@@ -0,0 +1,24 @@
+@public
+Feature: Verifying if server returns order book
+
+    Scenario: Server should return order book of trading pair XBT/USD
+        Given Squid website is responding
+        When Public request for Depth is send with "pair=XBTUSD"
+        Then Response body contains Depth result
+        And Result has only entries for "XXBTZUSD"
+
+    Scenario: Server should return order book limited to requested count of entries
+        Given Squid website is responding
+        When Public request for Depth is send with "pair=XBTUSD&count=5"
+        Then Response body contains Depth result
+        And Result has at most 5 entries per pair
+
+    Scenario Outline: Server should return error on invalid order book request
+        Given Squid website is responding
+        When Public request for Depth is send with "<query>"
+        Then Response body contains "<error>" error
+
+        Examples:
+            | query                 | error                      |
+            | pair=NOTAPAIR         | EQuery:Unknown asset pair  |
+            | pair=XBTUSD&count=abc | EGeneral:Invalid arguments |
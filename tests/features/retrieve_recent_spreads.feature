@@ -0,0 +1,19 @@
+@public
+Feature: Verifying if server returns recent spreads
+
+    Scenario: Server should return recent spreads of trading pair XBT/USD
+        Given Squid website is responding
+        When Public request for Spread is send with "pair=XBTUSD"
+        Then Response body contains Spread result
+        And Result has only entries for "XXBTZUSD"
+
+    Scenario: Server should return only spreads newer than requested
+        Given Squid website is responding
+        When Public request for Spread is send with "pair=XBTUSD&since=1666000000"
+        Then Response body contains Spread result
+        And Result has only entries newer than 1666000000
+
+    Scenario: Server should return error on unknown trading pair
+        Given Squid website is responding
+        When Public request for Spread is send with "pair=NOTAPAIR"
+        Then Response body contains "EQuery:Unknown asset pair" error
@@ -0,0 +1,20 @@
+@public
+Feature: Verifying if server returns recent trades
+
+    Scenario: Server should return recent trades of trading pair XBT/USD
+        Given Squid website is responding
+        When Public request for Trades is send with "pair=XBTUSD"
+        Then Response body contains Trades result
+        And Result has only entries for "XXBTZUSD"
+
+    Scenario: Server should return only requested count of trades newer than requested
+        Given Squid website is responding
+        When Public request for Trades is send with "pair=XBTUSD&since=1666000000&count=2"
+        Then Response body contains Trades result
+        And Result has only entries newer than 1666000000
+        And Result has at most 2 entries per pair
+
+    Scenario: Server should return error on unknown trading pair
+        Given Squid website is responding
+        When Public request for Trades is send with "pair=NOTAPAIR"
+        Then Response body contains "EQuery:Unknown asset pair" error
@@ -1,7 +1,9 @@
+@public
 Feature: Verifying if server can retrieve current time
 
     Scenario: Server should return correct time
 
         Given Squid website is responding
         When Get request for server time is send
-        Then Server time is returned in the response body
\ No newline at end of file
+        Then Server time is returned in the response body
+        And the response arrived within 1000 ms
\ No newline at end of file
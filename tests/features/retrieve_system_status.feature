@@ -0,0 +1,8 @@
+@public
+Feature: Verifying if server returns its system status
+
+    Scenario: Server should return current system status
+        Given Squid website is responding
+        When Public request for SystemStatus is send
+        Then Response body contains SystemStatus result
+        And Reported system status is one of online, maintenance, cancel_only or post_only
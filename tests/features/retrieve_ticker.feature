@@ -0,0 +1,18 @@
+@public
+Feature: Verifying if server returns ticker informations
+
+    Scenario: Server should return ticker of all trading pairs
+        Given Squid website is responding
+        When Public request for Ticker is send
+        Then Response body contains Ticker result
+
+    Scenario: Server should return ticker only of requested trading pairs
+        Given Squid website is responding
+        When Public request for Ticker is send with "pair=XBTUSD,ETHXBT"
+        Then Response body contains Ticker result
+        And Result has only entries for "XXBTZUSD, XETHXXBT"
+
+    Scenario: Server should return error on unknown trading pair
+        Given Squid website is responding
+        When Public request for Ticker is send with "pair=NOTAPAIR"
+        Then Response body contains "EQuery:Unknown asset pair" error
@@ -1,9 +1,11 @@
+@public
 Feature: Verifying if server coretly resturns trading pair XBT/USD
 
     Scenario: Server should return trading pair XBT/USD
         Given Squid website is responding
         When Get request for XBT/USD trading pair is send
         Then All trading pair informations are in response body
+        And the response arrived within 1000 ms
 
     Scenario: Server should return traiding pair XBT/USD, but response is limited to leverage
         Given Squid website is responding
@@ -0,0 +1,22 @@
+@serial @trading @permission-modify-orders @permission-query-open-orders
+Feature: Verify if orders, which could trade on the account, are refused before they are signed
+
+    @real-orders
+    Scenario Outline: Order breaking <rule> rule should be refused
+        Given User has account, API KEY, and API secret
+        When Order to sell <volume> <pair> at <price> is placed
+        Then Order is refused by safety guard because of <rule>
+
+        Examples:
+            | volume | pair   | price   | rule            |
+            | 0.011  | XBTUSD | 1000000 | pair            |
+            | 0.011  | ETHXBT | market  | order_type      |
+            | 0.011  | ETHXBT | +0.01   | order_type      |
+            | 1      | ETHXBT | 0.5     | order_notional  |
+
+    # Ticker price of the fake exchange is known, so the order is placed just above it.
+    @real-orders @mock-only
+    Scenario: Order close to the market price should be refused
+        Given User has account, API KEY, and API secret
+        When Order to sell 0.011 ETHXBT at 0.07 is placed
+        Then Order is refused by safety guard because of market_distance
@@ -1,76 +1,108 @@
-use std::{collections::HashMap};
-
-use hmac::{Hmac, Mac};
-use indexmap::IndexMap;
-use reqwest::{self, StatusCode};
-use sha2::{Digest, Sha256, Sha512};
-mod helper_structs;
-use helper_structs::{
-    BaseResponse, CriticalErrorResponse, OrderDetails, ServerTimeType, TradingPairAll,
-    TradingPairFees, TradingPairLeverage, TradingPairMargin, TradingPairRuler, XBTUSDRequest,
+use std::{collections::HashMap, sync::OnceLock, time::Duration};
+
+use growing_squid::{
+    client::{
+        api_url, get_open_orders, public_request, set_api_url, validate_only, ApiResponse,
+        NonceGen, SignatureError, SignedRequest, SquidCreds, API_URL_ENV,
+    },
+    clock::{self, Clock, FakeClock, SystemClock},
+    http, latency, metrics,
+    models::{
+        BaseResponse, BatchOrderResult, CriticalErrorResponse, OpenOrders, ServerTimeType,
+        TradingPairAll, TradingPairFees, TradingPairLeverage, TradingPairMargin, XBTUSDRequest,
+    },
+    order::{ConstraintError, OrderRequest},
+    profile::{self, Profile},
+    safety::{self, SafetyError},
+    telemetry,
 };
+use reqwest::StatusCode;
+use rust_decimal::Decimal;
+use tracing::instrument;
+mod cleanup;
+mod environment;
+mod fake_exchange;
+mod helper_structs;
+mod steps;
+use fake_exchange::FakeExchange;
+use helper_structs::TradingPairRuler;
 
-use cucumber::{given, then, when, World as _};
+use cucumber::{cli, given, runner::ScenarioType, then, when, writer::Stats as _, World as _};
 
-const API_URL: &str = "https://api.kraken.com";
-const API_KEY_ENV: &str = "API_KEY";
-const API_SEC_ENV: &str = "API_SEC";
+const MOCK_ENV: &str = "SQUID_MOCK";
+const MONITOR_INTERVAL_ENV: &str = "SQUID_MONITOR_INTERVAL_SECS";
+const METRICS_ADDR_ENV: &str = "SQUID_METRICS_ADDR";
+const DEFAULT_METRICS_ADDR: &str = "127.0.0.1:9898";
+
+static PROFILE: OnceLock<Profile> = OnceLock::new();
+// Command line without --env flag, which cucumber does not know.
+static CUCUMBER_ARGS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Environment selected for the run.
+fn current_profile() -> &'static Profile {
+    PROFILE.get().expect("Environment is selected in main")
+}
 
 #[derive(cucumber::World, Debug, Default)]
 struct World {
+    scenario: String,
+    last_endpoint: String,
     last_response_text: String,
+    last_response_latency: Duration,
+    // Local times of sending the last Time request and receiving its response, in ms.
+    time_request_ms: Option<(i64, i64)>,
     creds: SquidCreds,
     nonce_gen: NonceGen,
-}
-
-#[derive(Debug, Default)]
-struct SquidCreds {
-    key: String,
-    secret: String,
-}
-
-impl SquidCreds {
-    fn load_from_env() -> SquidCreds {
-        SquidCreds {
-            key: std::env::var(API_KEY_ENV).expect("API_KEY not found in environment"),
-            secret: std::env::var(API_SEC_ENV).expect("API_SEC not fount in environment"),
-        }
-    }
-}
-
-#[derive(Debug, Default)]
-struct NonceGen {
-    last_nonce: i64,
-}
-
-impl NonceGen {
-    fn get_nonce(&mut self) -> String {
-        let mut nonce = chrono::Utc::now().timestamp() * 1000;
-        if nonce == self.last_nonce {
-            nonce += 1;
-        }
-        self.last_nonce = nonce;
-        nonce.to_string()
+    remembered_ids: Vec<String>,
+    // Txids of all orders placed by the scenario, cancelled after it.
+    placed_orders: Vec<String>,
+    // Dead man's switch may be armed by the scenario, it is disarmed after it.
+    dead_mans_switch_armed: bool,
+    // Orders placed by name given in the scenario, with their txid or error.
+    named_orders: HashMap<String, BatchOrderResult>,
+    // Description of the last order, which was only validated.
+    validated_description: Option<String>,
+    // Constraints of the pair from AssetPairs, against which orders are checked locally.
+    pair_constraints: Option<TradingPairAll>,
+    // Order checked against pair constraints, with the result of the check.
+    checked_order: Option<(OrderRequest, Result<(), ConstraintError>)>,
+    // Reason, why the last call was refused by the safety guard.
+    safety_error: Option<SafetyError>,
+    // Last request sent by authentication steps, which can be sent again.
+    last_signed: Option<SignedRequest>,
+    // Error of signing with the secret given by the scenario.
+    signature_error: Option<SignatureError>,
+}
+
+impl World {
+    fn store_response(&mut self, response: ApiResponse) {
+        self.last_response_text = response.text;
+        self.last_response_latency = response.latency;
     }
 }
 
 #[given("Squid website is responding")]
-async fn squid_is_responding(_w: &mut World) {
-    let resp = reqwest::get(API_URL).await;
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn squid_is_responding(w: &mut World) {
+    let resp = http::client().get(api_url()).send().await;
     assert!(resp.is_ok());
     let resp = resp.unwrap();
     assert_eq!(resp.status(), StatusCode::OK);
 }
 
 #[when("Get request for server time is send")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
 async fn send_get_time_request(w: &mut World) {
-    let resp = reqwest::get("https://api.kraken.com/0/public/Time")
+    let sent_ms = SystemClock.now_ms();
+    let response = public_request("/0/public/Time")
         .await
         .expect("Unable to send request for server time");
-    w.last_response_text = resp.text().await.unwrap();
+    w.time_request_ms = Some((sent_ms, SystemClock.now_ms()));
+    w.store_response(response);
 }
 
 #[then("Server time is returned in the response body")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
 async fn check_get_time_response(w: &mut World) {
     let body = serde_json::from_str::<BaseResponse<ServerTimeType>>(&w.last_response_text)
         .expect("Unable deserialize server response. Mesage may be malformed");
@@ -78,15 +110,20 @@ async fn check_get_time_response(w: &mut World) {
         body.error.is_empty(),
         "Found unexpexted error message in server time response"
     );
-    let current_time = chrono::Utc::now();
-    let unix_timestamp = current_time.timestamp();
-    let server_timestamp = body.result.unixtime;
-    let timestamp_diff = (unix_timestamp - server_timestamp).abs();
-    const MAX_ALLOWED_TIMESTAMP_DIFF: i64 = 5;
+    let (sent_ms, received_ms) = w
+        .time_request_ms
+        .expect("Time request is sent by the scenario");
+    let offset = clock::estimate(&[clock::Sample {
+        sent_ms,
+        server_secs: body.result.unixtime,
+        received_ms,
+    }])
+    .expect("Offset is estimated from one sample");
     assert!(
-        timestamp_diff < MAX_ALLOWED_TIMESTAMP_DIFF,
-        "Too much difference between server time and system time, diff: {timestamp_diff}. Server or testing environment may have incorrect time source"
+        offset.is_within(current_profile().max_clock_skew_secs * 1000),
+        "Too much difference between server time and system time, offset: {offset}. Server or testing environment may have incorrect time source"
     );
+    let server_timestamp = body.result.unixtime;
     let server_time = chrono::DateTime::<chrono::Utc>::from_utc(
         chrono::NaiveDateTime::from_timestamp(server_timestamp, 0),
         chrono::Utc,
@@ -98,45 +135,45 @@ async fn check_get_time_response(w: &mut World) {
     );
 }
 
-
 #[when("Get request for XBT/USD trading pair is send")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
 async fn send_xbt2usd_traiding_pair_request(w: &mut World) {
-    let resp = reqwest::get("https://api.kraken.com/0/public/AssetPairs?pair=XXBTZUSD")
-        .await
-        .expect("Some internal test problem. Cannot send request for XBT/USD traiding pair");
-    w.last_response_text = resp.text().await.unwrap();
+    w.store_response(
+        public_request("/0/public/AssetPairs?pair=XXBTZUSD")
+            .await
+            .expect("Some internal test problem. Cannot send request for XBT/USD traiding pair"),
+    );
 }
 
 #[then("All trading pair informations are in response body")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
 async fn check_traiding_pair_response(w: &mut World) {
     let body =
         serde_json::from_str::<BaseResponse<XBTUSDRequest<TradingPairAll>>>(&w.last_response_text)
             .expect("Cannot deserialize server response. Response body may be malformed.");
     assert!(body.error.is_empty());
-    let reference = TradingPairRuler {
-        aclass_base: "currency".to_string(),
-        aclass_quote: "currency".to_string(),
-        altname: "XBTUSD".to_string(),
-        base: "XXBT".to_string(),
-        fee_volume_currency: "ZUSD".to_string(),
-        lot: "unit".to_string(),
-        quote: "ZUSD".to_string(),
-        wsname: "XBT/USD".to_string(),
-    };
+    let reference = TradingPairRuler::from(
+        current_profile()
+            .reference
+            .get("XXBTZUSD")
+            .expect("Reference data of XXBTZUSD are missing in the environment"),
+    );
     let pair_data = body.result.XXBTZUSD;
     assert_eq!(pair_data, reference);
 }
 
 #[when("Get request for XBT/USD trading pair, limited to leverage info is send")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
 async fn send_xbt2usd_traiding_pair_request_leverage(w: &mut World) {
-    let resp =
-        reqwest::get("https://api.kraken.com/0/public/AssetPairs?pair=XXBTZUSD&info=leverage")
+    w.store_response(
+        public_request("/0/public/AssetPairs?pair=XXBTZUSD&info=leverage")
             .await
-            .expect("Some internal test problem. Cannot send request for XBT/USD traiding pair");
-    w.last_response_text = resp.text().await.unwrap();
+            .expect("Some internal test problem. Cannot send request for XBT/USD traiding pair"),
+    );
 }
 
 #[then("Leverage info for trading pair is in response body")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
 async fn check_traiding_pair_response_leverage(w: &mut World) {
     let body = serde_json::from_str::<BaseResponse<XBTUSDRequest<TradingPairLeverage>>>(
         &w.last_response_text,
@@ -145,16 +182,18 @@ async fn check_traiding_pair_response_leverage(w: &mut World) {
     assert!(body.error.is_empty());
 }
 
-
 #[when("Get request for XBT/USD trading pair, limited to fees info is send")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
 async fn send_xbt2usd_traiding_pair_request_fees(w: &mut World) {
-    let resp = reqwest::get("https://api.kraken.com/0/public/AssetPairs?pair=XXBTZUSD&info=fees")
-        .await
-        .expect("Some internal test problem. Cannot send request for XBT/USD traiding pair");
-    w.last_response_text = resp.text().await.unwrap();
+    w.store_response(
+        public_request("/0/public/AssetPairs?pair=XXBTZUSD&info=fees")
+            .await
+            .expect("Some internal test problem. Cannot send request for XBT/USD traiding pair"),
+    );
 }
 
 #[then("Fees info for trading pair is in response body")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
 async fn check_traiding_pair_response_fees(w: &mut World) {
     let body =
         serde_json::from_str::<BaseResponse<XBTUSDRequest<TradingPairFees>>>(&w.last_response_text)
@@ -162,16 +201,18 @@ async fn check_traiding_pair_response_fees(w: &mut World) {
     assert!(body.error.is_empty());
 }
 
-
 #[when("Get request for XBT/USD trading pair, limited to margin info is send")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
 async fn send_xbt2usd_traiding_pair_request_margin(w: &mut World) {
-    let resp = reqwest::get("https://api.kraken.com/0/public/AssetPairs?pair=XXBTZUSD&info=margin")
-        .await
-        .expect("Some internal test problem. Cannot send request for XBT/USD traiding pair");
-    w.last_response_text = resp.text().await.unwrap();
+    w.store_response(
+        public_request("/0/public/AssetPairs?pair=XXBTZUSD&info=margin")
+            .await
+            .expect("Some internal test problem. Cannot send request for XBT/USD traiding pair"),
+    );
 }
 
 #[then("Margin info for trading pair is in response body")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
 async fn check_traiding_pair_response_margin(w: &mut World) {
     let body = serde_json::from_str::<BaseResponse<XBTUSDRequest<TradingPairMargin>>>(
         &w.last_response_text,
@@ -180,163 +221,244 @@ async fn check_traiding_pair_response_margin(w: &mut World) {
     assert!(body.error.is_empty());
 }
 
-
 #[when("Get request for XBT/USD trading pair, with info query value info is send")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
 async fn send_xbt2usd_traiding_pair_request_info(w: &mut World) {
-    let resp = reqwest::get("https://api.kraken.com/0/public/AssetPairs?pair=XXBTZUSD&info=info")
-        .await
-        .expect("Some internal test problem. Cannot send request for XBT/USD traiding pair");
-    w.last_response_text = resp.text().await.unwrap();
+    w.store_response(
+        public_request("/0/public/AssetPairs?pair=XXBTZUSD&info=info")
+            .await
+            .expect("Some internal test problem. Cannot send request for XBT/USD traiding pair"),
+    );
 }
 
-
-
 #[when("Get request for XBT/USD trading pair, with info invalid value is send")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
 async fn send_xbt2usd_traiding_pair_request_invalid(w: &mut World) {
-    let resp =
-        reqwest::get("https://api.kraken.com/0/public/AssetPairs?pair=XXBTZUSD&info=invalid_value")
+    w.store_response(
+        public_request("/0/public/AssetPairs?pair=XXBTZUSD&info=invalid_value")
             .await
-            .expect("Some internal test problem. Cannot send request for XBT/USD traiding pair");
-    w.last_response_text = resp.text().await.unwrap();
+            .expect("Some internal test problem. Cannot send request for XBT/USD traiding pair"),
+    );
 }
 
 #[then("Response body contains Invalid argumants error")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
 async fn check_traiding_pair_response_invalid(w: &mut World) {
     let body = serde_json::from_str::<CriticalErrorResponse>(&w.last_response_text)
         .expect("Cannot deserialize server response. Response body may be malformed.");
     assert_eq!(body.error, vec!["EGeneral:Invalid arguments"]);
 }
 
-
-fn create_signature(secret: &str, urlpath: &str, postdata: &str, nonce: &str) -> String {
-    let msg_sha_input = nonce.to_string() + postdata;
-
-    let mut sha256 = Sha256::default();
-    sha256.update(&msg_sha_input.as_bytes());
-
-    let data_hash = sha256.finalize();
-
-    let mut hmac_input = urlpath.as_bytes().to_vec();
-    for elem in data_hash {
-        hmac_input.push(elem);
-    }
-
-    let hmac_key = base64::decode(secret.as_bytes()).unwrap();
-    let mut mac = Hmac::<Sha512>::new_from_slice(&hmac_key[..]).unwrap();
-    mac.update(&hmac_input);
-    base64::encode(&mac.finalize().into_bytes())
-}
-
-fn urlencode(data: &IndexMap<&str, String>) -> String {
-    let mut encoded = String::new();
-    for (key, val) in data {
-        encoded.push_str(&format!("{key}={val}&"));
-    }
-    encoded.pop();
-    encoded
-}
-
-async fn private_request(
-    uri_path: &str,
-    data: IndexMap<&str, String>,
-    api_key: &str,
-    api_secret: &str,
-) -> String {
-    let client = reqwest::Client::new();
-    let nonce = data.get("nonce").unwrap();
-    let data_urlencoded = urlencode(&data);
-    let signature = create_signature(api_secret, uri_path, &data_urlencoded, &nonce);
-
-    let request = client
-        .post(format!("{API_URL}{uri_path}"))
-        .header("API-Key", api_key)
-        .header("API-Sign", signature)
-        .body(data_urlencoded);
-    let response = request.send().await;
-    let body = response
-        .expect("Could not receive server response for OpenOrders")
-        .text()
-        .await;
-    body.expect("could not translate server response for OpenOrders request to text format")
-}
-
-async fn get_open_orders(creds: &SquidCreds, nonce_gen: &mut NonceGen) -> String {
-    let nonce = nonce_gen.get_nonce();
-    let data = IndexMap::from([("nonce", nonce), ("trades", "true".to_string())]);
-    private_request("/0/private/OpenOrders", data, &creds.key, &creds.secret).await
-}
-
-#[when("User add some order")]
-async fn add_order_step(w: &mut World) {
-    let _out = add_order(&w.creds, &mut w.nonce_gen).await;
-}
-
-async fn add_order(creds: &SquidCreds, nonce_gen: &mut NonceGen) -> String {
-    let nonce = nonce_gen.get_nonce();
-    let data = IndexMap::from([
-        ("nonce", nonce),
-        ("ordertype", "limit".to_string()),
-        ("type", "sell".to_string()),
-        ("volume", "0.011".to_string()),
-        ("pair", "ethxbt".to_string()),
-        ("price", "0.5".to_string()),
-    ]);
-    let out = private_request("/0/private/AddOrder", data, &creds.key, &creds.secret).await;
-    out
-}
-
-#[given("User has no open orders")]
-async fn cancel_all_orders_step(w: &mut World) {
-    cancel_all_orders(&w.creds, &mut w.nonce_gen).await;
-}
-
-async fn cancel_all_orders(creds: &SquidCreds, nonce_gen: &mut NonceGen) -> String {
-    let nonce = nonce_gen.get_nonce();
-    let data = IndexMap::from([("nonce", nonce)]);
-    private_request("/0/private/CancelAll", data, &creds.key, &creds.secret).await
-}
-
 #[given("User has account, API KEY, and API secret")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
 async fn log_into_account(w: &mut World) {
-    w.creds = SquidCreds::load_from_env();
+    w.creds = if fake_exchange::is_running() {
+        fake_exchange::credentials()
+    } else {
+        current_profile()
+            .credentials
+            .load()
+            .expect("API key and secret of the environment not found")
+    };
 }
 
 #[when("Request for list of orders is send")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
 async fn send_request_for_user_orders(w: &mut World) {
-    let out = get_open_orders(&w.creds, &mut w.nonce_gen).await;
-    w.last_response_text = out;
+    let out = get_open_orders(&w.creds, &mut w.nonce_gen)
+        .await
+        .expect("Could not receive server response for OpenOrders");
+    w.store_response(out);
 }
 
 #[then("There is description of one order in response body")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
 async fn check_user_orders_list_response(w: &mut World) {
-    let body =
-        serde_json::from_str::<BaseResponse<HashMap<String, HashMap<String, OrderDetails>>>>(
-            &w.last_response_text,
-        )
+    if validate_only() {
+        // Nothing is placed in dry run, so the description of validated order is checked instead.
+        assert_eq!(
+            w.validated_description.as_deref(),
+            Some("sell 0.01100000 ETHXBT @ limit 0.50000")
+        );
+        return;
+    }
+    let body = serde_json::from_str::<BaseResponse<OpenOrders>>(&w.last_response_text)
         .expect("Cannot deserialize server response. Response body may be malformed.");
 
-    let (_, details) = &body.result["open"]
-        .iter()
-        .next()
-        .expect("Test error there should be at least one open order");
+    // Only orders placed by this run are checked, the account may be shared.
+    let orders = steps::tagged_orders(body.result);
+    let [(_, details)] = orders.as_slice() else {
+        panic!("Expected one order of this run, found {}", orders.len());
+    };
     assert_eq!(details.status, "open".to_string());
-    assert_eq!(details.vol, "0.01100000".to_string());
+    assert_eq!(details.vol, Decimal::new(11, 3));
     assert_eq!(details.descr["pair"], "ETHXBT".to_string());
     assert_eq!(details.descr["price"], "0.50000".to_string());
 }
 
 #[then("There are not any orders in response body")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
 async fn check_user_orders_list_response_empty(w: &mut World) {
-    let body =
-        serde_json::from_str::<BaseResponse<HashMap<String, HashMap<String, OrderDetails>>>>(
-            &w.last_response_text,
-        )
+    let body = serde_json::from_str::<BaseResponse<OpenOrders>>(&w.last_response_text)
         .expect("Cannot deserialize server response. Response body may be malformed.");
 
-    assert_eq!(*&body.result["open"].len(), 0 as usize);
+    assert_eq!(steps::tagged_orders(body.result).len(), 0);
+}
+
+#[then(expr = "the response arrived within {int} ms")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_response_latency(w: &mut World, max_ms: u64) {
+    let latency_ms = w.last_response_latency.as_millis();
+    assert!(
+        latency_ms <= u128::from(max_ms),
+        "Response arrived after {latency_ms} ms, but it was expected within {max_ms} ms"
+    );
+}
+
+/// Runs all features once. Returns description of failures, if there were any.
+async fn run_suite() -> Option<String> {
+    safety::reset_run_notional();
+    // Nonces and order times follow server time, even when the local clock drifts.
+    if let Err(e) = clock::synchronize(&SystemClock).await {
+        tracing::warn!("clock offset to the server is unknown, local time is used: {e}");
+    }
+    let writer = World::cucumber()
+        // Signed requests have to arrive with growing nonces, so scenarios of @serial features don't run concurrently.
+        .which_scenario(|feature, _rule, scenario| {
+            if feature
+                .tags
+                .iter()
+                .chain(&scenario.tags)
+                .any(|tag| tag == "serial")
+            {
+                ScenarioType::Serial
+            } else {
+                ScenarioType::Concurrent
+            }
+        })
+        .before(|feature, _rule, scenario, w| {
+            Box::pin(async move {
+                w.scenario = format!("{} / {}", feature.name, scenario.name);
+            })
+        })
+        // Orders are cancelled and the dead man's switch is disarmed, even if the scenario failed before its own cleanup.
+        .after(|_feature, _rule, _scenario, w| {
+            Box::pin(async move {
+                if let Some(w) = w {
+                    // Disarmed first, so a failed scenario cannot cancel all orders of the account later.
+                    let disarmed = cleanup::disarm_dead_mans_switch(w).await;
+                    cleanup::cancel_scenario_orders(w).await;
+                    if let Err(reason) = disarmed {
+                        panic!(
+                            "Dead man's switch armed by {} is not disarmed: {reason}",
+                            w.scenario
+                        );
+                    }
+                }
+            })
+        })
+        .with_cli(<cli::Opts<_, _, _> as cli::Parser>::parse_from(
+            CUCUMBER_ARGS.get().expect("Command line is parsed in main"),
+        ))
+        // Scenarios, which cannot run in this environment, are reported as skipped.
+        .filter_run("tests/features", |feature, _rule, scenario| {
+            environment::is_runnable(feature, scenario)
+        })
+        .await;
+    latency::report();
+    let leaked = cleanup::report();
+    let skipped = environment::report();
+    metrics::count_run(
+        writer.scenarios.passed,
+        writer.scenarios.failed,
+        writer.scenarios.skipped + skipped,
+    );
+    (writer.execution_has_failed() || leaked > 0).then(|| {
+        format!(
+            "{} steps failed, {} parsing errors, {} hook errors, {leaked} orders leaked",
+            writer.failed_steps(),
+            writer.parsing_errors(),
+            writer.hook_errors()
+        )
+    })
+}
+
+/// Takes `--env <name>` (or `--env=<name>`) out of command line arguments.
+fn take_env_flag(mut args: impl Iterator<Item = String>) -> (Option<String>, Vec<String>) {
+    let mut env = None;
+    let mut rest = Vec::new();
+    while let Some(arg) = args.next() {
+        if arg == "--env" {
+            env = Some(
+                args.next()
+                    .expect("--env flag needs name of the environment"),
+            );
+        } else if let Some(name) = arg.strip_prefix("--env=") {
+            env = Some(name.to_string());
+        } else {
+            rest.push(arg);
+        }
+    }
+    (env, rest)
+}
+
+/// Synthetic monitoring of the API: runs the suite forever and exposes results on "/metrics" endpoint.
+async fn monitor(interval: Duration) {
+    let addr = std::env::var(METRICS_ADDR_ENV)
+        .unwrap_or_else(|_| DEFAULT_METRICS_ADDR.to_string())
+        .parse()
+        .expect("SQUID_METRICS_ADDR should be socket address, e.g. 127.0.0.1:9898");
+    metrics::serve(addr);
+    loop {
+        if let Some(failures) = run_suite().await {
+            tracing::warn!("monitoring run failed: {failures}");
+        }
+        tokio::time::sleep(interval).await;
+    }
 }
 
 #[tokio::main]
 async fn main() {
-    World::run("tests/features").await;
+    telemetry::init();
+    http::init().unwrap_or_else(|e| panic!("Cannot configure HTTP client: {e}"));
+    let (env_flag, args) = take_env_flag(std::env::args());
+    CUCUMBER_ARGS.get_or_init(|| args);
+    // SQUID_MOCK is a shortcut for the mock environment.
+    let name = profile::selected(env_flag.as_deref()).unwrap_or_else(|| {
+        match std::env::var_os(MOCK_ENV) {
+            Some(_) => "mock".to_string(),
+            None => "production".to_string(),
+        }
+    });
+    let profile = PROFILE.get_or_init(|| {
+        profile::load(&name).unwrap_or_else(|e| panic!("Cannot select environment: {e}"))
+    });
+    let _fake_exchange = profile.fake_exchange.then(|| {
+        let exchange = FakeExchange::start(FakeClock::skewed(profile.fake_clock_skew_ms));
+        set_api_url(exchange.url());
+        exchange
+    });
+    // SQUID_API_URL still overrides URL of the environment.
+    if let (false, Some(url), None) = (
+        profile.fake_exchange,
+        &profile.rest_url,
+        std::env::var_os(API_URL_ENV),
+    ) {
+        set_api_url(url.clone());
+    }
+    safety::set_live(profile.is_live());
+    environment::check_permissions().await;
+    match std::env::var(MONITOR_INTERVAL_ENV) {
+        Ok(interval) => {
+            let secs = interval
+                .parse()
+                .expect("SQUID_MONITOR_INTERVAL_SECS should be number of seconds");
+            monitor(Duration::from_secs(secs)).await;
+        }
+        Err(_) => {
+            if let Some(failures) = run_suite().await {
+                panic!("{failures}");
+            }
+        }
+    }
 }
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use cucumber::{then, when};
+use growing_squid::{
+    client::{
+        get_balance, get_closed_orders, get_extended_balance, get_ledgers, get_open_positions,
+        get_trade_balance, get_trade_volume, get_trades_history, query_ledgers, query_orders,
+        query_trades,
+    },
+    models::{PositionInfo, TradeVolume},
+};
+use serde_json::{Map, Value};
+use tracing::instrument;
+
+use super::{parse_params, parse_result};
+use crate::World;
+
+async fn send_private(w: &mut World, endpoint: &str, params: &[(&str, &str)]) {
+    let creds = &w.creds;
+    let nonce_gen = &mut w.nonce_gen;
+    let response = match endpoint {
+        "Balance" => get_balance(creds, nonce_gen).await,
+        "BalanceEx" => get_extended_balance(creds, nonce_gen).await,
+        "TradeBalance" => get_trade_balance(creds, nonce_gen, params).await,
+        "ClosedOrders" => get_closed_orders(creds, nonce_gen, params).await,
+        "QueryOrders" => query_orders(creds, nonce_gen, params).await,
+        "TradesHistory" => get_trades_history(creds, nonce_gen, params).await,
+        "QueryTrades" => query_trades(creds, nonce_gen, params).await,
+        "OpenPositions" => get_open_positions(creds, nonce_gen, params).await,
+        "Ledgers" => get_ledgers(creds, nonce_gen, params).await,
+        "QueryLedgers" => query_ledgers(creds, nonce_gen, params).await,
+        "TradeVolume" => get_trade_volume(creds, nonce_gen, params).await,
+        other => panic!("Test error: there is no client method for {other} endpoint"),
+    }
+    .unwrap_or_else(|e| panic!("Could not receive server response for {endpoint}: {e}"));
+    w.last_endpoint = endpoint.to_string();
+    w.store_response(response);
+}
+
+/// Entries of the last result by their ids. Lists with count, e.g. ClosedOrders, are unwrapped.
+fn result_entries(w: &World) -> Map<String, Value> {
+    let mut result = parse_result::<Map<String, Value>>(w);
+    let list = match w.last_endpoint.as_str() {
+        "ClosedOrders" => "closed",
+        "TradesHistory" => "trades",
+        "Ledgers" => "ledger",
+        _ => return result,
+    };
+    match result.remove(list) {
+        Some(Value::Object(entries)) => entries,
+        other => panic!("Expected {list} entries in the result, got {other:?}"),
+    }
+}
+
+#[when(expr = "Private request for {word} is send")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn send_private_request(w: &mut World, endpoint: String) {
+    send_private(w, &endpoint, &[]).await;
+}
+
+#[when(expr = "Private request for {word} is send with {string}")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn send_private_request_with_params(w: &mut World, endpoint: String, query: String) {
+    send_private(w, &endpoint, &parse_params(&query)).await;
+}
+
+#[when("Ids from the last result are remembered")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn remember_ids(w: &mut World) {
+    w.remembered_ids = result_entries(w).keys().cloned().collect();
+}
+
+#[when(expr = "Private request for {word} is send with remembered ids as {word}")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn send_private_request_with_ids(w: &mut World, endpoint: String, param: String) {
+    let ids = w.remembered_ids.join(",");
+    send_private(w, &endpoint, &[(&param, &ids)]).await;
+}
+
+#[then("Result has entries for remembered ids")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_remembered_ids(w: &mut World) {
+    let mut ids: Vec<String> = result_entries(w).keys().cloned().collect();
+    ids.sort_unstable();
+    let mut expected = w.remembered_ids.clone();
+    expected.sort_unstable();
+    assert_eq!(ids, expected);
+}
+
+#[then("Result count matches number of entries")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_result_count(w: &mut World) {
+    let count = parse_result::<Map<String, Value>>(w)["count"]
+        .as_u64()
+        .expect("There is no count in the result");
+    let entries = result_entries(w).len() as u64;
+    // Lists are paginated by 50 entries, count is the total number of them.
+    assert!(
+        entries == count || (entries == 50 && count > 50),
+        "Result has {entries} entries, but its count is {count}"
+    );
+}
+
+#[then(expr = "Every result entry has {word} {string}")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_entries_field(w: &mut World, field: String, expected: String) {
+    for (id, entry) in result_entries(w) {
+        assert_eq!(
+            entry[&field].as_str(),
+            Some(expected.as_str()),
+            "Entry {id} has unexpected {field}"
+        );
+    }
+}
+
+#[then(expr = "Open positions are returned {word} calculated value and net")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_positions_calcs(w: &mut World, with: String) {
+    let expected = match with.as_str() {
+        "with" => true,
+        "without" => false,
+        other => panic!("Test error: expected 'with' or 'without', got {other}"),
+    };
+    for (id, position) in parse_result::<HashMap<String, PositionInfo>>(w) {
+        assert_eq!(
+            position.value.is_some() && position.net.is_some(),
+            expected,
+            "Position {id} has unexpected calculations"
+        );
+    }
+}
+
+#[then(expr = "Result has taker and maker fee tiers for {string}")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_fee_tiers(w: &mut World, pair: String) {
+    let volume = parse_result::<TradeVolume>(w);
+    for (kind, tiers) in [("taker", volume.fees), ("maker", volume.fees_maker)] {
+        let tiers = tiers.unwrap_or_else(|| panic!("There are no {kind} fees in the result"));
+        assert!(
+            tiers.contains_key(&pair),
+            "There is no {kind} fee tier for {pair}"
+        );
+    }
+}
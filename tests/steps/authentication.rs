@@ -0,0 +1,114 @@
+//! Private requests with broken authentication, signed and sent without the client checks.
+
+use cucumber::{then, when};
+use growing_squid::client::{send_signed, sign_request, SignatureError, SignedRequest};
+use indexmap::IndexMap;
+use tracing::instrument;
+
+use crate::World;
+
+// base64 of "squid-wrong-api-secret"
+const WRONG_API_SECRET: &str = "c3F1aWQtd3JvbmctYXBpLXNlY3JldA==";
+
+/// TradeBalance params with given nonce, signed for the endpoint.
+fn sign(endpoint: &str, nonce: String, secret: &str) -> SignedRequest {
+    let data = IndexMap::from([("nonce", nonce), ("asset", "ZUSD".to_string())]);
+    sign_request(&format!("/0/private/{endpoint}"), &data, secret)
+        .expect("Test error: request cannot be signed")
+}
+
+async fn send(w: &mut World, endpoint: &str, signed: SignedRequest, with_key: bool) {
+    let api_key = with_key.then_some(w.creds.key.as_str());
+    let response = send_signed(signed.clone(), api_key)
+        .await
+        .expect("Could not receive server response for signed request");
+    w.last_endpoint = endpoint.to_string();
+    w.store_response(response);
+    w.last_signed = Some(signed);
+}
+
+fn nonce_of(signed: &SignedRequest) -> i64 {
+    signed
+        .body
+        .split('&')
+        .find_map(|param| param.strip_prefix("nonce="))
+        .and_then(|nonce| nonce.parse().ok())
+        .expect("Test error: signed request has no nonce")
+}
+
+#[when(expr = "{word} request is sent with correct signature")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn send_correct(w: &mut World, endpoint: String) {
+    let signed = sign(&endpoint, w.nonce_gen.get_nonce(), &w.creds.secret);
+    send(w, &endpoint, signed, true).await;
+}
+
+#[when(expr = "{word} request is sent with body changed after signing")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn send_tampered_body(w: &mut World, endpoint: String) {
+    let mut signed = sign(&endpoint, w.nonce_gen.get_nonce(), &w.creds.secret);
+    signed.body = signed.body.replace("asset=ZUSD", "asset=XXBT");
+    send(w, &endpoint, signed, true).await;
+}
+
+#[when(expr = "{word} request signed for {word} is sent")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn send_signed_for_other_path(w: &mut World, endpoint: String, signed_for: String) {
+    let mut signed = sign(&signed_for, w.nonce_gen.get_nonce(), &w.creds.secret);
+    signed.uri_path = format!("/0/private/{endpoint}");
+    send(w, &endpoint, signed, true).await;
+}
+
+#[when("The same request is sent again")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn send_again(w: &mut World) {
+    let signed = w
+        .last_signed
+        .clone()
+        .expect("Test error: no signed request was sent before");
+    let endpoint = w.last_endpoint.clone();
+    send(w, &endpoint, signed, true).await;
+}
+
+#[when(expr = "{word} request is sent with nonce lower than the last one")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn send_lower_nonce(w: &mut World, endpoint: String) {
+    let last = nonce_of(
+        w.last_signed
+            .as_ref()
+            .expect("Test error: no signed request was sent before"),
+    );
+    let signed = sign(&endpoint, (last - 1).to_string(), &w.creds.secret);
+    send(w, &endpoint, signed, true).await;
+}
+
+#[when(expr = "{word} request is sent signed with other secret")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn send_wrong_secret(w: &mut World, endpoint: String) {
+    let signed = sign(&endpoint, w.nonce_gen.get_nonce(), WRONG_API_SECRET);
+    send(w, &endpoint, signed, true).await;
+}
+
+#[when(expr = "{word} request is sent without API-Key header")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn send_without_key(w: &mut World, endpoint: String) {
+    let signed = sign(&endpoint, w.nonce_gen.get_nonce(), &w.creds.secret);
+    send(w, &endpoint, signed, false).await;
+}
+
+#[when(expr = "{word} request is signed with secret {string}")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn sign_with_secret(w: &mut World, endpoint: String, secret: String) {
+    let data = IndexMap::from([("nonce", w.nonce_gen.get_nonce())]);
+    w.signature_error = sign_request(&format!("/0/private/{endpoint}"), &data, &secret).err();
+}
+
+#[then("Request is not signed, because the secret is not valid base64")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_invalid_secret(w: &mut World) {
+    assert!(
+        matches!(w.signature_error, Some(SignatureError::InvalidSecret(_))),
+        "Expected invalid secret error, got {:?}",
+        w.signature_error
+    );
+}
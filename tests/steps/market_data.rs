@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use cucumber::{then, when};
+use growing_squid::{
+    client::{
+        get_assets, get_ohlc, get_order_book, get_recent_spreads, get_recent_trades,
+        get_system_status, get_ticker,
+    },
+    models::{OhlcResult, OrderBook, SpreadResult, SystemStatus, TradesResult},
+};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use tracing::instrument;
+
+use super::{parse_params, parse_result};
+use crate::World;
+
+async fn send_public(w: &mut World, endpoint: &str, params: &[(&str, &str)]) {
+    let response = match endpoint {
+        "SystemStatus" => get_system_status().await,
+        "Assets" => get_assets(params).await,
+        "Ticker" => get_ticker(params).await,
+        "OHLC" => get_ohlc(params).await,
+        "Depth" => get_order_book(params).await,
+        "Trades" => get_recent_trades(params).await,
+        "Spread" => get_recent_spreads(params).await,
+        other => panic!("Test error: there is no client method for {other} endpoint"),
+    }
+    .unwrap_or_else(|e| {
+        panic!("Some internal test problem. Cannot send request for {endpoint}: {e}")
+    });
+    w.last_endpoint = endpoint.to_string();
+    w.store_response(response);
+}
+
+#[when(expr = "Public request for {word} is send")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn send_public_request(w: &mut World, endpoint: String) {
+    send_public(w, &endpoint, &[]).await;
+}
+
+#[when(expr = "Public request for {word} is send with {string}")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn send_public_request_with_query(w: &mut World, endpoint: String, query: String) {
+    send_public(w, &endpoint, &parse_params(&query)).await;
+}
+
+#[then("Reported system status is one of online, maintenance, cancel_only or post_only")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_system_status(w: &mut World) {
+    let status = parse_result::<SystemStatus>(w);
+    assert!(
+        ["online", "maintenance", "cancel_only", "post_only"].contains(&status.status.as_str()),
+        "Unknown system status {}",
+        status.status
+    );
+}
+
+#[then(expr = "Result has only entries for {string}")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_result_entries(w: &mut World, expected: String) {
+    let result = parse_result::<HashMap<String, Value>>(w);
+    let mut keys: Vec<&str> = result
+        .keys()
+        .map(String::as_str)
+        .filter(|key| *key != "last")
+        .collect();
+    keys.sort_unstable();
+    let mut expected: Vec<&str> = expected.split(',').map(str::trim).collect();
+    expected.sort_unstable();
+    assert_eq!(keys, expected);
+}
+
+#[then(expr = "Result has at most {int} entries per pair")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_result_count(w: &mut World, max: usize) {
+    let counts: Vec<usize> = match w.last_endpoint.as_str() {
+        "OHLC" => parse_result::<OhlcResult>(w)
+            .pairs
+            .values()
+            .map(Vec::len)
+            .collect(),
+        "Trades" => parse_result::<TradesResult>(w)
+            .pairs
+            .values()
+            .map(Vec::len)
+            .collect(),
+        "Spread" => parse_result::<SpreadResult>(w)
+            .pairs
+            .values()
+            .map(Vec::len)
+            .collect(),
+        "Depth" => parse_result::<HashMap<String, OrderBook>>(w)
+            .values()
+            .flat_map(|book| [book.asks.len(), book.bids.len()])
+            .collect(),
+        other => panic!("Test error: {other} result is not a list of entries"),
+    };
+    assert!(!counts.is_empty(), "There are no pairs in the result");
+    assert!(
+        counts.iter().all(|count| *count <= max),
+        "Expected at most {max} entries, got {counts:?}"
+    );
+}
+
+#[then(expr = "Result has only entries newer than {int}")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_result_since(w: &mut World, since: i64) {
+    let times: Vec<Decimal> = match w.last_endpoint.as_str() {
+        "OHLC" => parse_result::<OhlcResult>(w)
+            .pairs
+            .values()
+            .flatten()
+            .map(|entry| Decimal::from(entry.0))
+            .collect(),
+        "Trades" => parse_result::<TradesResult>(w)
+            .pairs
+            .values()
+            .flatten()
+            .map(|entry| entry.2)
+            .collect(),
+        "Spread" => parse_result::<SpreadResult>(w)
+            .pairs
+            .values()
+            .flatten()
+            .map(|entry| Decimal::from(entry.0))
+            .collect(),
+        other => panic!("Test error: {other} result has no timestamps"),
+    };
+    assert!(
+        times.iter().all(|time| *time >= Decimal::from(since)),
+        "Found entries older than {since}: {times:?}"
+    );
+}
@@ -0,0 +1,96 @@
+mod account_data;
+mod authentication;
+mod market_data;
+mod orders;
+
+pub(crate) use orders::tagged_orders;
+
+use std::collections::HashMap;
+
+use cucumber::then;
+use growing_squid::models::{
+    AddOrderBatchResult, AddOrderResult, AmendOrderResult, AssetInfo, BaseResponse,
+    CancelAllOrdersAfterResult, CancelOrderResult, ClosedOrders, CriticalErrorResponse,
+    EditOrderResult, ExtendedBalance, LedgerEntry, Ledgers, OhlcResult, OrderBook, OrderInfo,
+    PositionInfo, SpreadResult, SystemStatus, TickerInfo, TradeBalance, TradeInfo, TradeVolume,
+    TradesHistory, TradesResult,
+};
+use serde::de::DeserializeOwned;
+use tracing::instrument;
+
+use crate::World;
+
+/// Deserializes typed result of the last response and checks, that there are no errors in it.
+fn parse_result<T: DeserializeOwned>(w: &World) -> T {
+    let body = serde_json::from_str::<BaseResponse<T>>(&w.last_response_text)
+        .expect("Cannot deserialize server response. Response body may be malformed.");
+    assert!(
+        body.error.is_empty(),
+        "Found unexpected errors in {} response: {:?}",
+        w.last_endpoint,
+        body.error
+    );
+    body.result
+}
+
+/// Splits query from feature file, e.g. "pair=XBTUSD&count=10", into request parameters.
+fn parse_params(query: &str) -> Vec<(&str, &str)> {
+    query
+        .split('&')
+        .map(|param| {
+            param
+                .split_once('=')
+                .unwrap_or_else(|| panic!("Test error: '{param}' should have key=value format"))
+        })
+        .collect()
+}
+
+#[then(expr = "Response body contains {word} result")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_result(w: &mut World, endpoint: String) {
+    assert_eq!(
+        endpoint, w.last_endpoint,
+        "Test error: checked result is not from the last request"
+    );
+    match endpoint.as_str() {
+        "SystemStatus" => drop(parse_result::<SystemStatus>(w)),
+        "Assets" => drop(parse_result::<HashMap<String, AssetInfo>>(w)),
+        "Ticker" => drop(parse_result::<HashMap<String, TickerInfo>>(w)),
+        "OHLC" => drop(parse_result::<OhlcResult>(w)),
+        "Depth" => drop(parse_result::<HashMap<String, OrderBook>>(w)),
+        "Trades" => drop(parse_result::<TradesResult>(w)),
+        "Spread" => drop(parse_result::<SpreadResult>(w)),
+        "Balance" => drop(parse_result::<HashMap<String, String>>(w)),
+        "BalanceEx" => drop(parse_result::<HashMap<String, ExtendedBalance>>(w)),
+        "TradeBalance" => drop(parse_result::<TradeBalance>(w)),
+        "ClosedOrders" => drop(parse_result::<ClosedOrders>(w)),
+        "QueryOrders" => drop(parse_result::<HashMap<String, OrderInfo>>(w)),
+        "TradesHistory" => drop(parse_result::<TradesHistory>(w)),
+        "QueryTrades" => drop(parse_result::<HashMap<String, TradeInfo>>(w)),
+        "OpenPositions" => drop(parse_result::<HashMap<String, PositionInfo>>(w)),
+        "Ledgers" => drop(parse_result::<Ledgers>(w)),
+        "QueryLedgers" => drop(parse_result::<HashMap<String, LedgerEntry>>(w)),
+        "TradeVolume" => drop(parse_result::<TradeVolume>(w)),
+        "AddOrder" => drop(parse_result::<AddOrderResult>(w)),
+        "AddOrderBatch" => drop(parse_result::<AddOrderBatchResult>(w)),
+        "CancelOrderBatch" => drop(parse_result::<CancelOrderResult>(w)),
+        "EditOrder" => drop(parse_result::<EditOrderResult>(w)),
+        "AmendOrder" => drop(parse_result::<AmendOrderResult>(w)),
+        "CancelOrder" => drop(parse_result::<CancelOrderResult>(w)),
+        "CancelAllOrdersAfter" => drop(parse_result::<CancelAllOrdersAfterResult>(w)),
+        other => panic!("Test error: there is no model for {other} result"),
+    }
+}
+
+#[then(expr = "Response body contains {string} error")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_error(w: &mut World, expected: String) {
+    let body = serde_json::from_str::<CriticalErrorResponse>(&w.last_response_text)
+        .expect("Cannot deserialize server response. Response body may be malformed.");
+    // Server may add details after the message, e.g. "EGeneral:Invalid arguments:count".
+    assert!(
+        body.error.iter().any(|error| error.starts_with(&expected)),
+        "Expected {expected} error, but server responded with {:?}",
+        body.error
+    );
+}
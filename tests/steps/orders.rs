@@ -0,0 +1,729 @@
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    hash::BuildHasher,
+    sync::OnceLock,
+};
+
+use cucumber::{gherkin::Step, given, then, when};
+use growing_squid::{
+    client::{
+        add_order, add_order_batch, add_order_checked, amend_order, cancel_all_orders_after,
+        cancel_order, cancel_order_batch, edit_order, get_asset_pairs, get_open_orders,
+        query_orders, validate_only, ClientError,
+    },
+    clock::Clock,
+    models::{
+        AddOrderBatchResult, AddOrderResult, BaseResponse, BatchOrderResult,
+        CancelAllOrdersAfterResult, CancelOrderResult, EditOrderResult, OpenOrders, OrderDetails,
+        TradingPairAll,
+    },
+    order::{ConstraintError, OrderRequest, OrderTime, OrderType, Side},
+};
+use rust_decimal::Decimal;
+use serde_json::{Map, Value};
+use tracing::instrument;
+
+use super::{parse_params, parse_result};
+use crate::{cleanup, World};
+
+/// Userref of every order placed by this run. Cleanup cancels only orders with it,
+/// so other orders on a shared account are left untouched.
+pub(crate) fn run_userref() -> i32 {
+    static RUN_USERREF: OnceLock<i32> = OnceLock::new();
+    *RUN_USERREF.get_or_init(|| {
+        // Runs started in the same second differ by process id, and RandomState is seeded randomly in every process.
+        let seed = (std::process::id(), chrono::Utc::now().timestamp_nanos());
+        let hash = RandomState::new().hash_one(seed);
+        // Positive and non-zero, so it fits into int32 of the API and is not taken as no userref.
+        (hash % i32::MAX as u64) as i32 + 1
+    })
+}
+
+/// Open orders placed by this run.
+pub(crate) fn tagged_orders(orders: OpenOrders) -> Vec<(String, OrderDetails)> {
+    orders
+        .open
+        .into_iter()
+        .filter(|(_, order)| i64::from(order.userref) == i64::from(run_userref()))
+        .collect()
+}
+
+fn remembered_order(w: &World) -> String {
+    w.remembered_ids
+        .first()
+        .expect("Test error: there is no remembered order")
+        .clone()
+}
+
+/// Sell of 0.011 ETHXBT @ limit 0.5, far above the market price.
+fn some_order() -> OrderRequest {
+    OrderRequest::new(
+        Side::Sell,
+        "ethxbt",
+        "0.011",
+        OrderType::Limit {
+            price: "0.5".to_string(),
+        },
+    )
+    .userref(run_userref())
+}
+
+async fn place_order(w: &mut World, order: &OrderRequest) {
+    let response = add_order(&w.creds, &mut w.nonce_gen, order)
+        .await
+        .expect("Could not receive server response for AddOrder");
+    w.last_endpoint = "AddOrder".to_string();
+    w.store_response(response);
+}
+
+async fn add_some_order(w: &mut World, order: OrderRequest) {
+    place_order(w, &order).await;
+    let result = parse_result::<AddOrderResult>(w);
+    if validate_only() {
+        assert!(
+            result.txid.is_none(),
+            "Validated order should not be placed"
+        );
+        w.remembered_ids.clear();
+        w.validated_description = Some(result.descr.order);
+    } else {
+        w.remembered_ids = result.txid.expect("There is no txid of added order");
+        w.placed_orders.extend(w.remembered_ids.iter().cloned());
+    }
+}
+
+#[when("User add some order")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn add_order_step(w: &mut World) {
+    add_some_order(w, some_order()).await;
+}
+
+/// Order time of the example row, which can be also "server+<seconds>" after current server time.
+fn order_time(clock: &dyn Clock, val: &str) -> OrderTime {
+    match val.strip_prefix("server+") {
+        Some(secs) => {
+            OrderTime::at_server_time(clock, secs.parse().expect("Test error: invalid order time"))
+        }
+        None => val.parse().expect("Test error: invalid order time"),
+    }
+}
+
+/// Order of the example row. Options are the remaining params, e.g. "oflags=post&leverage=2". Order times are
+/// read from the clock.
+fn example_order(
+    clock: &dyn Clock,
+    side: &str,
+    ordertype: &str,
+    price: &str,
+    price2: &str,
+    options: &str,
+) -> OrderRequest {
+    let params: HashMap<&str, &str> = if options.is_empty() {
+        HashMap::new()
+    } else {
+        parse_params(options).into_iter().collect()
+    };
+    let order_type = |name: &str, price: &str, price2: &str| {
+        let (price, price2) = (price.to_string(), price2.to_string());
+        match name {
+            "market" => OrderType::Market,
+            "limit" => OrderType::Limit { price },
+            "iceberg" => OrderType::Iceberg {
+                price,
+                display_volume: params["displayvol"].to_string(),
+            },
+            "stop-loss" => OrderType::StopLoss { trigger: price },
+            "take-profit" => OrderType::TakeProfit { trigger: price },
+            "stop-loss-limit" => OrderType::StopLossLimit {
+                trigger: price,
+                limit: price2,
+            },
+            "take-profit-limit" => OrderType::TakeProfitLimit {
+                trigger: price,
+                limit: price2,
+            },
+            "trailing-stop" => OrderType::TrailingStop { offset: price },
+            "trailing-stop-limit" => OrderType::TrailingStopLimit {
+                offset: price,
+                limit_offset: price2,
+            },
+            "settle-position" => OrderType::SettlePosition,
+            other => panic!("Test error: unknown order type {other}"),
+        }
+    };
+    let side = side.parse().expect("Test error: invalid side");
+    let mut order = OrderRequest::new(
+        side,
+        "ETHXBT",
+        "0.011",
+        order_type(ordertype, price, price2),
+    );
+    // Orders with cl_ord_id cannot have userref, they are only validated anyway.
+    if !params.contains_key("cl_ord_id") {
+        order = order.userref(run_userref());
+    }
+    for (key, val) in &params {
+        order = match *key {
+            "displayvol" => order,
+            "oflags" => val.split(',').fold(order, |order, flag| {
+                order.oflag(flag.parse().expect("Test error: invalid oflags"))
+            }),
+            "timeinforce" => {
+                order.time_in_force(val.parse().expect("Test error: invalid timeinforce"))
+            }
+            "starttm" => order.start(order_time(clock, val)),
+            "expiretm" => order.expire(order_time(clock, val)),
+            "leverage" => order.leverage(val.parse().expect("Test error: invalid leverage")),
+            "reduce_only" => order.reduce_only(),
+            "userref" => order.userref(val.parse().expect("Test error: invalid userref")),
+            "cl_ord_id" => order.cl_ord_id(val),
+            "close[ordertype]" => order.close(order_type(
+                val,
+                params.get("close[price]").unwrap_or(&""),
+                params.get("close[price2]").unwrap_or(&""),
+            )),
+            "close[price]" | "close[price2]" => order,
+            other => panic!("Test error: unknown order option {other}"),
+        };
+    }
+    order
+}
+
+#[when(expr = "Order {word} {word} at {string} {string} with {string} is validated")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn validate_example_order(
+    w: &mut World,
+    side: String,
+    ordertype: String,
+    price: String,
+    price2: String,
+    options: String,
+) {
+    let order = example_order(
+        w.nonce_gen.clock(),
+        &side,
+        &ordertype,
+        &price,
+        &price2,
+        &options,
+    )
+    .validate();
+    place_order(w, &order).await;
+}
+
+#[then(expr = "Validated order description is {string}")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_order_description(w: &mut World, expected: String) {
+    let result = parse_result::<AddOrderResult>(w);
+    assert_eq!(result.descr.order, expected);
+    assert!(
+        result.txid.is_none(),
+        "Validated order should not be placed"
+    );
+}
+
+#[then(expr = "Validated close order description is {string}")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_close_description(w: &mut World, expected: String) {
+    let result = parse_result::<AddOrderResult>(w);
+    assert_eq!(result.descr.close.as_deref(), Some(expected.as_str()));
+}
+
+#[when(expr = "Remembered order is edited with {string}")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn edit_remembered_order(w: &mut World, query: String) {
+    let txid = remembered_order(w);
+    // New order, which replaces the edited one, has to keep the tag of the run.
+    let userref = run_userref().to_string();
+    let mut params = vec![("txid", txid.as_str()), ("userref", userref.as_str())];
+    params.extend(parse_params(&query));
+    let response = edit_order(&w.creds, &mut w.nonce_gen, &params)
+        .await
+        .expect("Could not receive server response for EditOrder");
+    w.last_endpoint = "EditOrder".to_string();
+    w.store_response(response);
+    // Edited order is replaced by a new one.
+    if let Some(txid) = parse_result::<EditOrderResult>(w).txid {
+        w.placed_orders.push(txid.clone());
+        w.remembered_ids = vec![txid];
+    }
+}
+
+#[when(expr = "Remembered order is amended with {string}")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn amend_remembered_order(w: &mut World, query: String) {
+    let txid = remembered_order(w);
+    let mut params = vec![("txid", txid.as_str())];
+    params.extend(parse_params(&query));
+    let response = amend_order(&w.creds, &mut w.nonce_gen, &params)
+        .await
+        .expect("Could not receive server response for AmendOrder");
+    w.last_endpoint = "AmendOrder".to_string();
+    w.store_response(response);
+}
+
+#[when("Remembered order is cancelled")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn cancel_remembered_order(w: &mut World) {
+    let txid = remembered_order(w);
+    let response = cancel_order(&w.creds, &mut w.nonce_gen, &txid)
+        .await
+        .expect("Could not receive server response for CancelOrder");
+    w.last_endpoint = "CancelOrder".to_string();
+    w.store_response(response);
+}
+
+async fn cancel_tagged_orders(w: &mut World) {
+    let response = cancel_order(&w.creds, &mut w.nonce_gen, &run_userref().to_string())
+        .await
+        .expect("Could not receive server response for CancelOrder");
+    w.last_endpoint = "CancelOrder".to_string();
+    w.store_response(response);
+}
+
+#[when("Orders of this run are cancelled")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn cancel_run_orders(w: &mut World) {
+    cancel_tagged_orders(w).await;
+}
+
+#[given("User has no open orders")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn cancel_orders_of_run(w: &mut World) {
+    cancel_tagged_orders(w).await;
+    // Nothing to cancel is not an error, only the count is 0.
+    parse_result::<CancelOrderResult>(w);
+}
+
+#[when(expr = "Order {string} is cancelled")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn cancel_order_by_id(w: &mut World, txid: String) {
+    let response = cancel_order(&w.creds, &mut w.nonce_gen, &txid)
+        .await
+        .expect("Could not receive server response for CancelOrder");
+    w.last_endpoint = "CancelOrder".to_string();
+    w.store_response(response);
+}
+
+#[then(expr = "Cancelled order count is {int}")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_cancelled_count(w: &mut World, expected: u32) {
+    assert_eq!(parse_result::<CancelOrderResult>(w).count, expected);
+}
+
+#[when(expr = "Dead man's switch is armed for {int} seconds")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn arm_dead_mans_switch(w: &mut World, timeout: u32) {
+    // Counted as armed, until it is certainly disarmed.
+    if timeout > 0 {
+        w.dead_mans_switch_armed = true;
+    }
+    let response = cancel_all_orders_after(&w.creds, &mut w.nonce_gen, timeout)
+        .await
+        .expect("Could not receive server response for CancelAllOrdersAfter");
+    if timeout == 0 && cleanup::is_success(&response.text) {
+        w.dead_mans_switch_armed = false;
+    }
+    w.last_endpoint = "CancelAllOrdersAfter".to_string();
+    w.store_response(response);
+}
+
+#[when("Dead man's switch is disarmed")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn disarm_dead_mans_switch(w: &mut World) {
+    arm_dead_mans_switch(w, 0).await;
+}
+
+#[then(expr = "Dead man's switch is reported as {word}")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_dead_mans_switch(w: &mut World, state: String) {
+    let result = parse_result::<CancelAllOrdersAfterResult>(w);
+    let armed = result.trigger_time != "0";
+    match state.as_str() {
+        "armed" => assert!(armed, "Dead man's switch is not armed"),
+        "disarmed" => assert!(
+            !armed,
+            "Dead man's switch will trigger at {}",
+            result.trigger_time
+        ),
+        other => panic!("Test error: expected armed or disarmed, got {other}"),
+    }
+}
+
+#[when(expr = "{int} seconds have passed")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn wait_seconds(w: &mut World, secs: u64) {
+    tokio::time::sleep(std::time::Duration::from_secs(secs)).await;
+}
+
+async fn query_remembered_order(w: &mut World) -> (String, Value) {
+    let txid = remembered_order(w);
+    let response = query_orders(&w.creds, &mut w.nonce_gen, &[("txid", &txid)])
+        .await
+        .expect("Could not receive server response for QueryOrders");
+    w.last_endpoint = "QueryOrders".to_string();
+    w.store_response(response);
+    let mut orders = parse_result::<Map<String, Value>>(w);
+    let order = orders
+        .remove(&txid)
+        .expect("Remembered order is not returned");
+    (txid, order)
+}
+
+/// Description holds requested values, e.g. limit price, top level fields are the order state.
+fn order_field<'a>(order: &'a Value, field: &str) -> &'a Value {
+    order["descr"].get(field).unwrap_or(&order[field])
+}
+
+#[then(expr = "Remembered order has {word} {string}")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_remembered_order(w: &mut World, field: String, expected: String) {
+    let (txid, order) = query_remembered_order(w).await;
+    assert_eq!(
+        order_field(&order, &field).as_str(),
+        Some(expected.as_str()),
+        "Order {txid} has unexpected {field}"
+    );
+}
+
+#[then(expr = "Remembered order {word} equals {word}")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_remembered_order_number(w: &mut World, field: String, expected: Decimal) {
+    let (txid, order) = query_remembered_order(w).await;
+    let pair = order["descr"]["pair"].as_str().expect("Order has no pair");
+    let pair = known_pair(w, pair).await;
+    // Numbers are compared with precision used by the exchange for the pair.
+    let (name, decimals) = match field.as_str() {
+        "volume" => ("vol", pair.lot_decimals.into()),
+        "price" => ("price", pair.pair_decimals.into()),
+        "cost" => ("cost", pair.cost_decimals),
+        other => panic!("Test error: {other} is not a number of the order"),
+    };
+    assert!(
+        expected.normalize().scale() <= decimals,
+        "Test error: {expected} has more than {decimals} decimals"
+    );
+    let value: Decimal = serde_json::from_value(order_field(&order, name).clone())
+        .unwrap_or_else(|e| panic!("{name} of order {txid} is not a number: {e}"));
+    assert_eq!(
+        value.round_dp(decimals),
+        expected,
+        "Order {txid} has unexpected {field}"
+    );
+}
+
+async fn check_open_orders(w: &mut World, contain: &str, txid: &str) {
+    let response = get_open_orders(&w.creds, &mut w.nonce_gen)
+        .await
+        .expect("Could not receive server response for OpenOrders");
+    w.last_endpoint = "OpenOrders".to_string();
+    w.store_response(response);
+    let is_open = parse_result::<OpenOrders>(w).open.contains_key(txid);
+    match contain {
+        "contain" => assert!(is_open, "Order {txid} is not open"),
+        "omit" => assert!(!is_open, "Order {txid} is still open"),
+        other => panic!("Test error: expected contain or omit, got {other}"),
+    }
+}
+
+#[then(expr = "Open orders {word} remembered order")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_open_remembered_order(w: &mut World, contain: String) {
+    let txid = remembered_order(w);
+    check_open_orders(w, &contain, &txid).await;
+}
+
+#[then(expr = "Open orders {word} order {string}")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_open_named_order(w: &mut World, contain: String, name: String) {
+    let txid = placed_order(w, &name);
+    check_open_orders(w, &contain, &txid).await;
+}
+
+/// Txid of the named order, which should be placed.
+fn placed_order(w: &World, name: &str) -> String {
+    let order = named_order(w, name);
+    if let Some(e) = &order.error {
+        panic!("Order {name} was rejected with {e}");
+    }
+    order
+        .txid
+        .clone()
+        .unwrap_or_else(|| panic!("Order {name} has no txid, it was only validated"))
+}
+
+fn named_order<'a>(w: &'a World, name: &str) -> &'a BatchOrderResult {
+    w.named_orders
+        .get(name)
+        .unwrap_or_else(|| panic!("Test error: there is no order named {name}"))
+}
+
+async fn submit_batch(w: &mut World, pair: &str, names: Vec<String>, orders: &[Vec<(&str, &str)>]) {
+    let userref = run_userref().to_string();
+    let orders: Vec<Vec<(&str, &str)>> = orders
+        .iter()
+        .map(|order| [order.as_slice(), &[("userref", userref.as_str())]].concat())
+        .collect();
+    let response = add_order_batch(&w.creds, &mut w.nonce_gen, &[("pair", pair)], &orders)
+        .await
+        .expect("Could not receive server response for AddOrderBatch");
+    w.last_endpoint = "AddOrderBatch".to_string();
+    w.store_response(response);
+    let body = serde_json::from_str::<BaseResponse<AddOrderBatchResult>>(&w.last_response_text);
+    // The whole batch may be rejected, then there are no results of single orders.
+    let Ok(body) = body else {
+        return;
+    };
+    assert_eq!(
+        body.result.orders.len(),
+        names.len(),
+        "Every order in the batch should have its result"
+    );
+    for (name, result) in names.into_iter().zip(body.result.orders) {
+        w.placed_orders.extend(result.txid.clone());
+        w.named_orders.insert(name, result);
+    }
+}
+
+#[when(expr = "User submits batch of orders for {string}")]
+#[instrument(skip(w, step), fields(scenario = %w.scenario))]
+async fn submit_named_batch(w: &mut World, pair: String, step: &Step) {
+    let table = step
+        .table
+        .as_ref()
+        .expect("Test error: orders of the batch should be given in a table");
+    let (header, rows) = table
+        .rows
+        .split_first()
+        .expect("Test error: table of orders has no header");
+    let name_column = header
+        .iter()
+        .position(|column| column == "name")
+        .expect("Test error: table of orders has no name column");
+    let names: Vec<String> = rows.iter().map(|row| row[name_column].clone()).collect();
+    let orders: Vec<Vec<(&str, &str)>> = rows
+        .iter()
+        .map(|row| {
+            header
+                .iter()
+                .zip(row)
+                .filter(|(column, _)| *column != "name")
+                .map(|(column, val)| (column.as_str(), val.as_str()))
+                .collect()
+        })
+        .collect();
+    submit_batch(w, &pair, names, &orders).await;
+}
+
+#[when(expr = "User submits batch of {int} copies of some order")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn submit_batch_of_copies(w: &mut World, count: usize) {
+    let order = vec![
+        ("ordertype", "limit"),
+        ("type", "sell"),
+        ("volume", "0.011"),
+        ("price", "0.5"),
+    ];
+    let names = (0..count).map(|i| format!("copy {i}")).collect();
+    submit_batch(w, "ETHXBT", names, &vec![order; count]).await;
+}
+
+#[then(expr = "Order {string} is placed")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_order_placed(w: &mut World, name: String) {
+    if !validate_only() {
+        placed_order(w, &name);
+        return;
+    }
+    // Validated orders are only described.
+    let order = named_order(w, &name);
+    assert_eq!(order.error, None, "Order {name} was rejected");
+    assert!(order.descr.is_some(), "Order {name} has no description");
+}
+
+#[then(expr = "Order {string} is rejected with {string}")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_order_rejected(w: &mut World, name: String, expected: String) {
+    let order = named_order(w, &name);
+    match (&order.error, &order.txid) {
+        (Some(e), _) => assert!(
+            e.starts_with(&expected),
+            "Order {name} was rejected with {e}, but {expected} was expected"
+        ),
+        (None, Some(txid)) => panic!("Order {name} was placed as {txid}"),
+        (None, None) => panic!("Order {name} was accepted by validation"),
+    }
+}
+
+#[when(expr = "Batch of orders {string} is cancelled")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn cancel_named_batch(w: &mut World, names: String) {
+    let txids: Vec<String> = names
+        .split(',')
+        .map(|name| placed_order(w, name.trim()))
+        .collect();
+    let txids: Vec<&str> = txids.iter().map(String::as_str).collect();
+    let response = cancel_order_batch(&w.creds, &mut w.nonce_gen, &txids)
+        .await
+        .expect("Could not receive server response for CancelOrderBatch");
+    w.last_endpoint = "CancelOrderBatch".to_string();
+    w.store_response(response);
+}
+
+/// Constraints of the pair from AssetPairs, which are requested once per scenario.
+async fn known_pair<'a>(w: &'a mut World, pair: &str) -> &'a TradingPairAll {
+    let known = w.pair_constraints.as_ref();
+    if !known.is_some_and(|known| known.altname.eq_ignore_ascii_case(pair)) {
+        let response = get_asset_pairs(&[("pair", pair)])
+            .await
+            .expect("Request for AssetPairs failed");
+        w.store_response(response);
+        w.last_endpoint = "AssetPairs".to_string();
+        let pairs = parse_result::<HashMap<String, TradingPairAll>>(w);
+        w.pair_constraints = pairs.into_values().next();
+    }
+    w.pair_constraints
+        .as_ref()
+        .unwrap_or_else(|| panic!("Pair {pair} is not returned by AssetPairs"))
+}
+
+#[given(expr = "Constraints of pair {string} are known")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn load_pair_constraints(w: &mut World, pair: String) {
+    known_pair(w, &pair).await;
+}
+
+#[when(expr = "Order to sell {word} {word} at {word} is checked against pair constraints")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_order_constraints(w: &mut World, volume: String, pair: String, price: String) {
+    let order = OrderRequest::new(Side::Sell, &pair, &volume, OrderType::Limit { price });
+    let pair = w
+        .pair_constraints
+        .as_ref()
+        .expect("Test error: constraints of the pair are not known");
+    let result = order.check(pair);
+    w.checked_order = Some((order, result));
+}
+
+#[when(
+    expr = "{word} order to sell {word} {word} triggered at {word} with limit {word} is checked against pair constraints"
+)]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_stop_limit_order_constraints(
+    w: &mut World,
+    ordertype: String,
+    volume: String,
+    pair: String,
+    trigger: String,
+    limit: String,
+) {
+    let order_type = match ordertype.as_str() {
+        "stop-loss-limit" => OrderType::StopLossLimit { trigger, limit },
+        "take-profit-limit" => OrderType::TakeProfitLimit { trigger, limit },
+        other => panic!("Test error: {other} is not a stop limit order type"),
+    };
+    let order = OrderRequest::new(Side::Sell, &pair, &volume, order_type);
+    let pair = w
+        .pair_constraints
+        .as_ref()
+        .expect("Test error: constraints of the pair are not known");
+    let result = order.check(pair);
+    w.checked_order = Some((order, result));
+}
+
+#[given(expr = "Constraints of pair {string} are known with tick size {word}")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn load_pair_constraints_with_tick_size(w: &mut World, pair: String, tick_size: String) {
+    known_pair(w, &pair).await;
+    let tick_size = tick_size
+        .parse()
+        .expect("Test error: tick size should be a number");
+    if let Some(pair) = w.pair_constraints.as_mut() {
+        pair.tick_size = Some(tick_size);
+    }
+}
+
+#[when(expr = "Order to sell {word} {word} at {word} is sent with local check")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn send_checked_order(w: &mut World, volume: String, pair: String, price: String) {
+    let order = OrderRequest::new(Side::Sell, &pair, &volume, OrderType::Limit { price })
+        .userref(run_userref())
+        .validate();
+    let pair = w
+        .pair_constraints
+        .as_ref()
+        .expect("Test error: constraints of the pair are not known");
+    let result = match add_order_checked(&w.creds, &mut w.nonce_gen, &order, pair).await {
+        Err(ClientError::Constraint(e)) => Err(e),
+        response => {
+            w.last_endpoint = "AddOrder".to_string();
+            w.store_response(response.expect("Could not receive server response for AddOrder"));
+            Ok(())
+        }
+    };
+    w.checked_order = Some((order, result));
+}
+
+fn checked_order(w: &World) -> &(OrderRequest, Result<(), ConstraintError>) {
+    w.checked_order
+        .as_ref()
+        .expect("Test error: there is no checked order")
+}
+
+#[then("Order passes local check")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_order_passed(w: &mut World) {
+    if let (order, Err(e)) = checked_order(w) {
+        panic!("Order {order:?} was rejected locally: {e}");
+    }
+}
+
+#[then(expr = "Order is rejected locally by {word} rule")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_order_rejected_locally(w: &mut World, rule: String) {
+    match checked_order(w) {
+        (_, Err(e)) => assert_eq!(e.rule(), rule, "Order was rejected because {e}"),
+        (order, Ok(())) => panic!("Order {order:?} passed local check"),
+    }
+}
+
+#[when("Checked order is validated by the server")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn validate_checked_order(w: &mut World) {
+    // Local check is bypassed, the server should reject the order on its own.
+    let order = checked_order(w).0.clone().validate();
+    place_order(w, &order).await;
+}
+
+#[when(expr = "Order to sell {word} {word} at {word} is placed")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn place_guarded_order(w: &mut World, volume: String, pair: String, price: String) {
+    let order_type = match price.as_str() {
+        "market" => OrderType::Market,
+        _ => OrderType::Limit { price },
+    };
+    let order = OrderRequest::new(Side::Sell, &pair, &volume, order_type).userref(run_userref());
+    match add_order(&w.creds, &mut w.nonce_gen, &order).await {
+        Err(ClientError::Unsafe(e)) => w.safety_error = Some(e),
+        response => {
+            w.last_endpoint = "AddOrder".to_string();
+            w.store_response(response.expect("Could not receive server response for AddOrder"));
+            // Order, which should have been refused, is still cancelled after the scenario.
+            if let Ok(body) =
+                serde_json::from_str::<BaseResponse<AddOrderResult>>(&w.last_response_text)
+            {
+                w.placed_orders.extend(body.result.txid.unwrap_or_default());
+            }
+        }
+    }
+}
+
+#[then(expr = "Order is refused by safety guard because of {word}")]
+#[instrument(skip(w), fields(scenario = %w.scenario))]
+async fn check_order_refused(w: &mut World, rule: String) {
+    let e = w
+        .safety_error
+        .as_ref()
+        .expect("Order was sent, but it should be refused before signing");
+    assert_eq!(e.rule(), rule, "Order was refused because {e}");
+}